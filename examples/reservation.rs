@@ -225,7 +225,7 @@ fn main() {
     for quantity in &[Quantity::new(1), Quantity::new(0)] {
         let new_reservation = new_reservation_with_quantity(*quantity);
         match Reservation::validated_from(new_reservation) {
-            Ok(reservation) => {
+            Ok((reservation, _warnings)) => {
                 debug_assert!(reservation.is_valid());
                 process_reservation(&reservation);
             }
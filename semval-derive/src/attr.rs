@@ -0,0 +1,303 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Parsing of `#[semval(...)]` attributes
+
+use syn::{
+    Attribute, Expr, Ident, LitStr, Path, Result, Token, Type, meta::ParseNestedMeta,
+    punctuated::Punctuated, token,
+};
+
+const ATTR_NAME: &str = "semval";
+
+/// Attributes of the annotated `struct` or `enum`
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    /// `#[semval(invalidity = Type)]`
+    pub(crate) invalidity: Option<Type>,
+
+    /// `#[semval(invalidity_derive(Trait, ...))]`
+    pub(crate) invalidity_derive: Vec<Path>,
+}
+
+impl ContainerAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut this = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident(ATTR_NAME)) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("invalidity") {
+                    this.invalidity = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("invalidity_derive") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    this.invalidity_derive
+                        .extend(Punctuated::<Path, Token![,]>::parse_terminated(&content)?);
+                    return Ok(());
+                }
+                Err(meta.error("unsupported container attribute"))
+            })?;
+        }
+        Ok(this)
+    }
+}
+
+/// Attributes of an `enum` variant
+#[derive(Default)]
+pub(crate) struct VariantAttrs {
+    /// `#[semval(skip)]`
+    pub(crate) skip: bool,
+}
+
+impl VariantAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut this = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident(ATTR_NAME)) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    this.skip = true;
+                    return Ok(());
+                }
+                Err(meta.error("unsupported variant attribute"))
+            })?;
+        }
+        Ok(this)
+    }
+}
+
+/// Attributes of a field
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    /// `#[semval(skip)]`
+    pub(crate) skip: bool,
+
+    /// `#[semval(variant = Ident)]`
+    pub(crate) variant: Option<Ident>,
+
+    /// `#[semval(skip_from)]`
+    pub(crate) skip_from: bool,
+
+    /// `#[semval(nested)]`
+    pub(crate) nested: bool,
+
+    /// `#[semval(length(...), range(...), ...)]`
+    pub(crate) constraints: Vec<ConstraintAttr>,
+}
+
+impl FieldAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut this = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident(ATTR_NAME)) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    this.skip = true;
+                    return Ok(());
+                }
+                if meta.path.is_ident("skip_from") {
+                    this.skip_from = true;
+                    return Ok(());
+                }
+                if meta.path.is_ident("variant") {
+                    this.variant = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("nested") {
+                    this.nested = true;
+                    return Ok(());
+                }
+                if let Some(constraint) = ConstraintAttr::parse(&meta)? {
+                    this.constraints.push(constraint);
+                    return Ok(());
+                }
+                Err(meta.error("unsupported field attribute"))
+            })?;
+        }
+        Ok(this)
+    }
+}
+
+/// Attributes for `#[derive(NestedInvalidity)]`
+#[derive(Default)]
+pub(crate) struct NestedAttrs {
+    /// `#[semval(source)]` on a field
+    pub(crate) source: bool,
+
+    /// `#[semval(leaf)]` on the type or an `enum` variant
+    pub(crate) leaf: bool,
+}
+
+impl NestedAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut this = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident(ATTR_NAME)) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("source") {
+                    this.source = true;
+                    return Ok(());
+                }
+                if meta.path.is_ident("leaf") {
+                    this.leaf = true;
+                    return Ok(());
+                }
+                Err(meta.error("unsupported attribute"))
+            })?;
+        }
+        Ok(this)
+    }
+}
+
+/// A constraint on the value of a field
+pub(crate) enum Constraint {
+    /// `length(min = ..., max = ...)`
+    Length {
+        min: Option<Expr>,
+        max: Option<Expr>,
+    },
+
+    /// `range(min = ..., max = ...)`
+    Range {
+        min: Option<Expr>,
+        max: Option<Expr>,
+    },
+
+    /// `non_empty`
+    NonEmpty,
+
+    /// `pattern = "..."` or `pattern(regex = "...")`
+    Pattern(LitStr),
+
+    /// `required`
+    Required,
+
+    /// `custom = function` or `custom(function = ...)`
+    Custom(Path),
+}
+
+impl Constraint {
+    /// The suffix of the corresponding invalidity variant
+    pub(crate) const fn variant_suffix(&self) -> &'static str {
+        match self {
+            Self::Length { .. } => "Length",
+            Self::Range { .. } => "Range",
+            Self::NonEmpty => "NonEmpty",
+            Self::Pattern(_) => "Pattern",
+            Self::Required => "Required",
+            Self::Custom(_) => "Custom",
+        }
+    }
+}
+
+/// A constraint with an optional `variant = Ident` parameter
+pub(crate) struct ConstraintAttr {
+    pub(crate) constraint: Constraint,
+    pub(crate) variant: Option<Ident>,
+}
+
+impl ConstraintAttr {
+    fn parse(meta: &ParseNestedMeta<'_>) -> Result<Option<Self>> {
+        let mut variant = None;
+        let constraint = if meta.path.is_ident("length") || meta.path.is_ident("range") {
+            let mut min = None;
+            let mut max = None;
+            meta.parse_nested_meta(|param| {
+                if param.path.is_ident("min") {
+                    min = Some(param.value()?.parse()?);
+                    return Ok(());
+                }
+                if param.path.is_ident("max") {
+                    max = Some(param.value()?.parse()?);
+                    return Ok(());
+                }
+                parse_variant(&param, &mut variant)
+            })?;
+            if min.is_none() && max.is_none() {
+                return Err(meta.error("expected `min` and/or `max`"));
+            }
+            if meta.path.is_ident("length") {
+                Constraint::Length { min, max }
+            } else {
+                Constraint::Range { min, max }
+            }
+        } else if meta.path.is_ident("non_empty") || meta.path.is_ident("required") {
+            if meta.input.peek(token::Paren) {
+                meta.parse_nested_meta(|param| parse_variant(&param, &mut variant))?;
+            }
+            if meta.path.is_ident("non_empty") {
+                Constraint::NonEmpty
+            } else {
+                Constraint::Required
+            }
+        } else if meta.path.is_ident("pattern") {
+            if meta.input.peek(Token![=]) {
+                Constraint::Pattern(parse_regex(meta.value()?.parse()?)?)
+            } else {
+                let mut regex = None;
+                meta.parse_nested_meta(|param| {
+                    if param.path.is_ident("regex") {
+                        regex = Some(parse_regex(param.value()?.parse()?)?);
+                        return Ok(());
+                    }
+                    parse_variant(&param, &mut variant)
+                })?;
+                let Some(regex) = regex else {
+                    return Err(meta.error("expected `regex`"));
+                };
+                Constraint::Pattern(regex)
+            }
+        } else if meta.path.is_ident("custom") {
+            if meta.input.peek(Token![=]) {
+                Constraint::Custom(meta.value()?.parse()?)
+            } else {
+                let mut function = None;
+                meta.parse_nested_meta(|param| {
+                    if param.path.is_ident("function") {
+                        function = Some(param.value()?.parse()?);
+                        return Ok(());
+                    }
+                    parse_variant(&param, &mut variant)
+                })?;
+                let Some(function) = function else {
+                    return Err(meta.error("expected `function`"));
+                };
+                Constraint::Custom(function)
+            }
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            constraint,
+            variant,
+        }))
+    }
+}
+
+/// Reject invalid regular expressions at compile time instead
+/// of failing on the first validation at runtime
+#[cfg(feature = "regex")]
+fn parse_regex(regex: LitStr) -> Result<LitStr> {
+    match regex::Regex::new(&regex.value()) {
+        Ok(_) => Ok(regex),
+        Err(err) => Err(syn::Error::new(
+            regex.span(),
+            format!("invalid regular expression: {err}"),
+        )),
+    }
+}
+
+/// Regular expressions could only be matched with the `regex` feature
+#[cfg(not(feature = "regex"))]
+fn parse_regex(regex: LitStr) -> Result<LitStr> {
+    Err(syn::Error::new_spanned(
+        regex,
+        "`pattern` requires the `regex` feature of semval",
+    ))
+}
+
+fn parse_variant(meta: &ParseNestedMeta<'_>, variant: &mut Option<Ident>) -> Result<()> {
+    if meta.path.is_ident("variant") {
+        *variant = Some(meta.value()?.parse()?);
+        return Ok(());
+    }
+    Err(meta.error("unsupported constraint parameter"))
+}
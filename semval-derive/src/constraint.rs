@@ -0,0 +1,118 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Code generation for constraints on fields
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Expr, Type};
+
+use crate::{
+    attr::Constraint,
+    model::{Field, FieldConstraint},
+};
+
+fn optional(expr: Option<&Expr>) -> TokenStream {
+    expr.map_or_else(
+        || quote!(::core::option::Option::None),
+        |expr| quote!(::core::option::Option::Some(#expr)),
+    )
+}
+
+/// The type of the constraint that is wrapped into the invalidity variant
+pub(crate) fn payload_type(constraint: &Constraint, value_type: &Type) -> TokenStream {
+    match constraint {
+        Constraint::Length { .. } => quote!(::semval::constraint::Length),
+        Constraint::Range { .. } => quote!(::semval::constraint::Range<#value_type>),
+        Constraint::NonEmpty => quote!(::semval::constraint::NonEmpty),
+        Constraint::Pattern(_) => quote!(::semval::constraint::Pattern),
+        Constraint::Required => quote!(::semval::constraint::Required),
+        Constraint::Custom(_) => quote!(::semval::constraint::Custom),
+    }
+}
+
+/// Statements that check all constraints of a field
+///
+/// The `accessor` must evaluate to a reference of the field.
+pub(crate) fn check_field(field: &Field, accessor: &TokenStream, invalidity: &Type) -> TokenStream {
+    let value_type = field.value_type();
+    let checks = field.constraints.iter().map(
+        |FieldConstraint {
+             constraint,
+             variant,
+         }| {
+            let invalidate = |constraint_value: TokenStream, is_satisfied: TokenStream| {
+                quote! {
+                    let constraint = #constraint_value;
+                    context.invalidate_if(!(#is_satisfied), <#invalidity>::#variant(constraint))
+                }
+            };
+            let check = match constraint {
+                Constraint::Length { min, max } => {
+                    let min = optional(min.as_ref());
+                    let max = optional(max.as_ref());
+                    invalidate(
+                        quote!(::semval::constraint::Length { min: #min, max: #max }),
+                        quote!(constraint.is_satisfied_by(value)),
+                    )
+                }
+                Constraint::Range { min, max } => {
+                    let min = optional(min.as_ref());
+                    let max = optional(max.as_ref());
+                    invalidate(
+                        quote!(::semval::constraint::Range::<#value_type> { min: #min, max: #max }),
+                        quote!(constraint.is_satisfied_by(value)),
+                    )
+                }
+                Constraint::NonEmpty => invalidate(
+                    quote!(::semval::constraint::NonEmpty),
+                    quote!(constraint.is_satisfied_by(value)),
+                ),
+                Constraint::Pattern(regex) => invalidate(
+                    quote!(::semval::constraint::Pattern { regex: #regex }),
+                    quote! {{
+                        static REGEX: ::semval::__private::LazyLock<::semval::__private::Regex> =
+                            ::semval::__private::LazyLock::new(|| {
+                                ::semval::__private::Regex::new(#regex)
+                                    .expect("valid regular expression")
+                            });
+                        REGEX.is_match(::core::convert::AsRef::<str>::as_ref(value))
+                    }},
+                ),
+                Constraint::Custom(function) => {
+                    let name = quote!(#function).to_string().replace(' ', "");
+                    invalidate(
+                        quote!(::semval::constraint::Custom { function: #name }),
+                        quote!(#function(value)),
+                    )
+                }
+                Constraint::Required => {
+                    let check = invalidate(
+                        quote!(::semval::constraint::Required),
+                        quote!(constraint.is_satisfied_by(#accessor)),
+                    );
+                    return quote! {
+                        let context = { #check };
+                    };
+                }
+            };
+            if field.option_type().is_some() {
+                // Constraints only apply to present values
+                quote! {
+                    let context = match ::core::option::Option::as_ref(#accessor) {
+                        ::core::option::Option::Some(value) => { #check }
+                        ::core::option::Option::None => context,
+                    };
+                }
+            } else {
+                quote! {
+                    let context = {
+                        let value = #accessor;
+                        #check
+                    };
+                }
+            }
+        },
+    );
+    quote!( #( #checks )* )
+}
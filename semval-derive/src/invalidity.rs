@@ -0,0 +1,235 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Code generation for `#[derive(Invalidity)]`
+
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens as _, quote};
+use syn::{
+    DeriveInput, GenericArgument, Ident, Lifetime, Member, PathArguments, Result, Type, TypeArray,
+    TypeGroup, TypeParen, TypePath, TypeReference, TypeSlice, visit_mut::VisitMut,
+};
+
+use crate::{
+    constraint,
+    model::{Container, Field, FieldConstraint},
+};
+
+/// Replaces all lifetimes with `'static`
+///
+/// Invalidity types must not borrow any data. The associated
+/// invalidity types of fields that borrow data are resolved
+/// for the `'static` lifetime instead.
+struct StaticLifetimes;
+
+impl VisitMut for StaticLifetimes {
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        *lifetime = Lifetime::new("'static", Span::call_site());
+    }
+}
+
+/// Peels off all wrapper types that forward the invalidity type
+/// of their elements
+///
+/// The associated invalidity types of these wrappers are resolved
+/// by blanket implementations that could not be normalized during
+/// the coherence check of the generated `From` conversions.
+fn element_type(ty: &Type) -> &Type {
+    match ty {
+        Type::Reference(TypeReference { elem, .. })
+        | Type::Slice(TypeSlice { elem, .. })
+        | Type::Array(TypeArray { elem, .. })
+        | Type::Paren(TypeParen { elem, .. })
+        | Type::Group(TypeGroup { elem, .. }) => element_type(elem),
+        Type::Path(TypePath { qself: None, path }) => {
+            let Some(segment) = path.segments.last() else {
+                return ty;
+            };
+            let PathArguments::AngleBracketed(args) = &segment.arguments else {
+                return ty;
+            };
+            let mut type_args = args.args.iter().filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            });
+            let element = match segment.ident.to_string().as_str() {
+                "Option" | "Vec" | "VecDeque" | "BTreeSet" | "HashSet" | "Box" | "Rc" | "Arc"
+                | "Cow" => type_args.next(),
+                // Only the values of maps are validated
+                "BTreeMap" | "HashMap" => type_args.nth(1),
+                _ => None,
+            };
+            element.map_or(ty, element_type)
+        }
+        _ => ty,
+    }
+}
+
+fn static_type(ty: &Type) -> Type {
+    let mut ty = ty.clone();
+    StaticLifetimes.visit_type_mut(&mut ty);
+    ty
+}
+
+/// A variant of the generated invalidity type
+struct InvalidityVariant {
+    ident: Ident,
+    payload: TokenStream,
+    doc: String,
+}
+
+fn invalidity_variants(fields: &[&Field]) -> Vec<InvalidityVariant> {
+    let mut variants = Vec::with_capacity(fields.len());
+    for field in fields {
+        let name = match &field.member {
+            Member::Named(ident) => ident.to_string(),
+            Member::Unnamed(index) => index.index.to_string(),
+        };
+        if let Some(ident) = &field.nested {
+            let ty = static_type(&field.ty);
+            variants.push(InvalidityVariant {
+                ident: ident.clone(),
+                payload: quote!(<#ty as ::semval::Validate>::Invalidity),
+                doc: format!("Invalid `{name}`"),
+            });
+        }
+        let value_type = static_type(field.value_type());
+        for FieldConstraint {
+            constraint,
+            variant,
+        } in &field.constraints
+        {
+            variants.push(InvalidityVariant {
+                ident: variant.clone(),
+                payload: constraint::payload_type(constraint, &value_type),
+                doc: format!(
+                    "Violated `{}` constraint of `{name}`",
+                    constraint.variant_suffix()
+                ),
+            });
+        }
+    }
+    variants
+}
+
+pub(crate) fn derive(input: &DeriveInput) -> Result<TokenStream> {
+    let container = Container::from_input(input)?;
+    let vis = &input.vis;
+    let ident = container.invalidity_ident()?;
+    let type_params = container
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect::<Vec<_>>();
+    let generics = if type_params.is_empty() {
+        quote!()
+    } else {
+        quote!(< #( #type_params ),* >)
+    };
+
+    let fields = container.fields().collect::<Vec<_>>();
+    let predicates = fields
+        .iter()
+        .filter(|field| field.nested.is_some() && container.is_generic_type(&field.ty))
+        .map(|field| {
+            let ty = static_type(&field.ty);
+            quote!(#ty: ::semval::Validate)
+        })
+        .collect::<Vec<_>>();
+    let where_clause = if predicates.is_empty() {
+        quote!()
+    } else {
+        quote!(where #( #predicates ),*)
+    };
+
+    let variants = invalidity_variants(&fields);
+    let variant_idents = variants.iter().map(|variant| &variant.ident);
+    let variant_payloads = variants.iter().map(|variant| &variant.payload);
+    let variant_docs = variants.iter().map(|variant| &variant.doc);
+    let enum_doc = format!("Invalidities of [`{}`]", container.ident);
+    let derives = &container.attrs.invalidity_derive;
+    let derive_attr = if derives.is_empty() {
+        quote!()
+    } else {
+        quote!(#[derive( #( #derives ),* )])
+    };
+
+    let debug_arms = variants.iter().map(|InvalidityVariant { ident, .. }| {
+        let name = ident.to_string();
+        quote! {
+            Self::#ident(invalidity) => f.debug_tuple(#name).field(invalidity).finish(),
+        }
+    });
+    let debug_body = if variants.is_empty() {
+        quote!(match *self {})
+    } else {
+        quote!(match self { #( #debug_arms )* })
+    };
+
+    let from_impls = from_impls(&container, &fields, |ty| {
+        quote! {
+            impl #generics ::core::convert::From<#ty> for #ident #generics #where_clause
+        }
+    });
+
+    Ok(quote! {
+        #[doc = #enum_doc]
+        #derive_attr
+        #vis enum #ident #generics #where_clause {
+            #(
+                #[doc = #variant_docs]
+                #variant_idents(#variant_payloads),
+            )*
+        }
+
+        #[automatically_derived]
+        impl #generics ::core::fmt::Debug for #ident #generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #debug_body
+            }
+        }
+
+        #( #from_impls )*
+    })
+}
+
+/// Generates the `From` conversions for all recursively validated fields
+///
+/// Conversions from generic types would conflict with the reflexive
+/// `impl<T> From<T> for T` and duplicate conversions from the same
+/// type would conflict with each other.
+fn from_impls(
+    container: &Container<'_>,
+    fields: &[&Field],
+    impl_header: impl Fn(&TokenStream) -> TokenStream,
+) -> Vec<TokenStream> {
+    let mut from_types = Vec::<String>::with_capacity(fields.len());
+    fields
+        .iter()
+        .filter_map(|field| {
+            let variant = field.nested.as_ref()?;
+            if field.skip_from || container.is_generic_type(&field.ty) {
+                return None;
+            }
+            let ty = static_type(element_type(&field.ty));
+            let key = ty.to_token_stream().to_string();
+            if from_types.contains(&key) {
+                return None;
+            }
+            from_types.push(key);
+            Some((variant, ty))
+        })
+        .map(|(variant, ty)| {
+            let invalidity = quote!(<#ty as ::semval::Validate>::Invalidity);
+            let impl_header = impl_header(&invalidity);
+            quote! {
+                #[automatically_derived]
+                #impl_header {
+                    fn from(from: #invalidity) -> Self {
+                        Self::#variant(from)
+                    }
+                }
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,143 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! # semval-derive
+//!
+//! Derive macros for [semval](https://docs.rs/semval).
+//!
+//! Please use the re-exports from the `semval` crate by enabling
+//! its `derive` feature instead of depending on this crate directly.
+
+use proc_macro::TokenStream;
+use syn::{DeriveInput, parse_macro_input};
+
+mod attr;
+mod constraint;
+mod invalidity;
+mod model;
+mod nested;
+mod validate;
+
+/// Derive `Validate` by recursively validating all fields
+///
+/// Each field is validated with `Context::validate_with()` and
+/// its invalidities are wrapped into a variant of the invalidity
+/// type that is named after the field in `UpperCamelCase`.
+///
+/// Alternatively, the values of fields could be checked against
+/// constraints from `semval::constraint`. Each constraint is recorded
+/// as an invalidity variant that is named after the field with the
+/// name of the constraint appended, e.g. `NameLength(Length)` for
+/// a field `name`. Fields with constraints are not validated
+/// recursively unless requested explicitly. Constraints on the
+/// only field of a tuple struct are named after the constraint.
+///
+/// The invalidities of named fields are recorded at the path of the
+/// field name, see `Context::scoped()`. Unnamed fields don't add
+/// a path segment.
+///
+/// `Validate::validate_within()` is implemented and respects the
+/// mode and limit of the context, e.g. fail-fast contexts skip all
+/// remaining fields after the first error. `Validate::validate()`
+/// forwards to it.
+///
+/// # Attributes
+///
+/// - `#[semval(invalidity = Type)]` on the type:
+///   The associated `Validate::Invalidity` type. Defaults to the
+///   name of the type with the suffix `Invalidity` that is
+///   parameterized by the same type parameters.
+/// - `#[semval(skip)]` on a field or `enum` variant:
+///   Exclude the field or variant from validation.
+/// - `#[semval(variant = Ident)]` on a field: The variant of the
+///   invalidity type, required for unnamed fields of tuple structs.
+/// - `#[semval(nested)]` on a field: Validate the field recursively
+///   in addition to its constraints.
+///
+/// # Constraints
+///
+/// All constraints on fields of type `Option` are only checked if
+/// a value is present, except for `required`. An optional parameter
+/// `variant = Ident` overrides the name of the invalidity variant.
+///
+/// - `length(min = ..., max = ...)`: The number of elements or
+///   characters, see `semval::constraint::HasLength`.
+/// - `range(min = ..., max = ...)`: Lower and upper bounds of the value.
+/// - `non_empty`: The value must not be empty.
+/// - `pattern = "..."` or `pattern(regex = "...")`: The string value
+///   must match a regular expression. Requires the `regex` feature.
+///   Invalid regular expressions are rejected at compile time.
+/// - `custom = function` or `custom(function = ...)`: A predicate
+///   function `fn(&T) -> bool` that returns `true` for valid values.
+/// - `required`: An `Option` must not be `None`.
+///
+/// Fields of `enum` variants are wrapped into the invalidity variant
+/// of the same name if the variant has only a single validated field.
+/// Otherwise the name of the field (or its index) is appended.
+#[proc_macro_derive(Validate, attributes(semval))]
+pub fn derive_validate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    validate::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive the invalidity type of a validated type
+///
+/// Generates an `enum` with one variant per recursively validated
+/// field that wraps the associated invalidity type of the field and
+/// one variant per constraint that wraps the constraint, named and
+/// selected exactly like for `#[derive(Validate)]`.
+/// `From` conversions are generated for all wrapped invalidity types,
+/// which allows to use `Context::validate()` instead of
+/// `Context::validate_with()`.
+///
+/// The generated type implements `Debug`. The type parameters of the
+/// annotated type are applied to the generated type and lifetimes are
+/// replaced by `'static`.
+///
+/// # Attributes
+///
+/// - `#[semval(invalidity = Ident)]` on the type: The name of the
+///   generated type. Defaults to the name of the type with the
+///   suffix `Invalidity`.
+/// - `#[semval(invalidity_derive(Trait, ...))]` on the type:
+///   Additional traits that should be derived for the generated type.
+/// - `#[semval(skip_from)]` on a field: Omit the `From` conversion
+///   for the invalidity type of this field, e.g. if another field
+///   already has the same invalidity type.
+///
+/// Conversions are omitted implicitly for fields with a generic type
+/// and for repeated occurrences of the same element type, e.g. for
+/// fields of type `T` and `Box<T>` or `Vec<T>`.
+#[proc_macro_derive(Invalidity, attributes(semval))]
+pub fn derive_invalidity(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    invalidity::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `NestedInvalidity` for an invalidity type
+///
+/// The only field of a tuple struct or tuple variant is the source
+/// of the invalidity, e.g. `Customer(CustomerInvalidity)`. All other
+/// variants have no source unless one of their fields is marked
+/// explicitly. The types of all sources must implement
+/// `NestedInvalidity`.
+///
+/// Could be combined with `#[derive(Invalidity)]` by adding
+/// `#[semval(invalidity_derive(semval::NestedInvalidity))]`.
+///
+/// # Attributes
+///
+/// - `#[semval(source)]` on a field: The source of the invalidity.
+/// - `#[semval(leaf)]` on the type or an `enum` variant: No source,
+///   even if there is only a single unnamed field.
+#[proc_macro_derive(NestedInvalidity, attributes(semval))]
+pub fn derive_nested_invalidity(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    nested::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
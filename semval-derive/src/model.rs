@@ -0,0 +1,318 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Intermediate representation of the annotated type
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{ToTokens as _, format_ident};
+use syn::{
+    Data, DeriveInput, Error, Fields, GenericArgument, Generics, Ident, Member, PathArguments,
+    Result, Type, TypePath, parse_quote,
+};
+
+use crate::attr::{Constraint, ConstraintAttr, ContainerAttrs, FieldAttrs, VariantAttrs};
+
+/// A field that is validated
+pub(crate) struct Field {
+    /// Accessor of the field within its `struct` or `enum` variant
+    pub(crate) member: Member,
+
+    /// Local binding when destructuring the field
+    pub(crate) binding: Ident,
+
+    pub(crate) ty: Type,
+
+    /// The variant of the invalidity type that wraps the invalidities
+    /// of this field if it is validated recursively
+    pub(crate) nested: Option<Ident>,
+
+    /// Omit the `From` conversion into the invalidity type
+    pub(crate) skip_from: bool,
+
+    /// Constraints on the value of this field
+    pub(crate) constraints: Vec<FieldConstraint>,
+}
+
+impl Field {
+    /// The type of the value if the field is optional
+    pub(crate) fn option_type(&self) -> Option<&Type> {
+        let Type::Path(TypePath { qself: None, path }) = &self.ty else {
+            return None;
+        };
+        let segment = path.segments.last()?;
+        if segment.ident != "Option" {
+            return None;
+        }
+        let PathArguments::AngleBracketed(args) = &segment.arguments else {
+            return None;
+        };
+        match args.args.first() {
+            Some(GenericArgument::Type(ty)) if args.args.len() == 1 => Some(ty),
+            _ => None,
+        }
+    }
+
+    /// The type of the value that is checked by constraints
+    pub(crate) fn value_type(&self) -> &Type {
+        self.option_type().unwrap_or(&self.ty)
+    }
+}
+
+/// A constraint with its invalidity variant
+pub(crate) struct FieldConstraint {
+    pub(crate) constraint: Constraint,
+    pub(crate) variant: Ident,
+}
+
+/// A variant of an `enum`
+pub(crate) struct Variant {
+    pub(crate) ident: Ident,
+
+    /// The recursively validated fields
+    pub(crate) fields: Vec<Field>,
+}
+
+pub(crate) enum Shape {
+    Struct(Vec<Field>),
+    Enum(Vec<Variant>),
+}
+
+pub(crate) struct Container<'a> {
+    pub(crate) ident: &'a Ident,
+    pub(crate) generics: &'a Generics,
+    pub(crate) attrs: ContainerAttrs,
+    pub(crate) shape: Shape,
+}
+
+impl<'a> Container<'a> {
+    pub(crate) fn from_input(input: &'a DeriveInput) -> Result<Self> {
+        let attrs = ContainerAttrs::parse(&input.attrs)?;
+        let shape = match &input.data {
+            Data::Struct(data) => Shape::Struct(struct_fields(&data.fields)?),
+            Data::Enum(data) => {
+                let mut variants = Vec::with_capacity(data.variants.len());
+                for variant in &data.variants {
+                    if VariantAttrs::parse(&variant.attrs)?.skip {
+                        continue;
+                    }
+                    variants.push(Variant {
+                        ident: variant.ident.clone(),
+                        fields: variant_fields(&variant.ident, &variant.fields)?,
+                    });
+                }
+                Shape::Enum(variants)
+            }
+            Data::Union(_) => {
+                return Err(Error::new(Span::call_site(), "unions are not supported"));
+            }
+        };
+        Ok(Self {
+            ident: &input.ident,
+            generics: &input.generics,
+            attrs,
+            shape,
+        })
+    }
+
+    /// The name of the invalidity type
+    ///
+    /// Defaults to the name of the annotated type with the
+    /// suffix `Invalidity`.
+    pub(crate) fn invalidity_ident(&self) -> Result<Ident> {
+        let Some(invalidity) = &self.attrs.invalidity else {
+            return Ok(format_ident!("{}Invalidity", self.ident));
+        };
+        if let Type::Path(TypePath { qself: None, path }) = invalidity
+            && let Some(segment) = path.segments.last()
+        {
+            return Ok(segment.ident.clone());
+        }
+        Err(Error::new_spanned(invalidity, "expected a type name"))
+    }
+
+    /// The invalidity type
+    ///
+    /// Defaults to the name of the annotated type with the suffix
+    /// `Invalidity`, parameterized by the same type parameters.
+    pub(crate) fn invalidity_type(&self) -> Type {
+        if let Some(invalidity) = &self.attrs.invalidity {
+            return invalidity.clone();
+        }
+        let ident = format_ident!("{}Invalidity", self.ident);
+        let type_params = self.generics.type_params().map(|param| &param.ident);
+        parse_quote!(#ident < #( #type_params ),* >)
+    }
+
+    /// Check if the type refers to any of the type parameters
+    pub(crate) fn is_generic_type(&self, ty: &Type) -> bool {
+        let type_params = self
+            .generics
+            .type_params()
+            .map(|param| &param.ident)
+            .collect::<Vec<_>>();
+        !type_params.is_empty() && mentions_any_ident(ty.to_token_stream(), &type_params)
+    }
+
+    /// All validated fields
+    pub(crate) fn fields(&self) -> Box<dyn Iterator<Item = &Field> + '_> {
+        match &self.shape {
+            Shape::Struct(fields) => Box::new(fields.iter()),
+            Shape::Enum(variants) => Box::new(variants.iter().flat_map(|v| v.fields.iter())),
+        }
+    }
+}
+
+fn struct_fields(fields: &Fields) -> Result<Vec<Field>> {
+    let mut validated = Vec::with_capacity(fields.len());
+    for (index, field) in fields.iter().enumerate() {
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        if attrs.skip {
+            continue;
+        }
+        let base_name = match &field.ident {
+            Some(ident) => Some(to_upper_camel_case(ident)),
+            // The invalidity variants of constraints on newtypes
+            // are named after the constraint
+            None if fields.len() == 1 => Some(String::new()),
+            None => None,
+        };
+        validated.push(new_field(
+            index,
+            field,
+            attrs,
+            |attrs| match (&attrs.variant, &field.ident) {
+                (Some(variant), _) => Ok(variant.clone()),
+                (None, Some(ident)) => Ok(format_ident!("{}", to_upper_camel_case(ident))),
+                (None, None) => Err(Error::new_spanned(
+                    field,
+                    "unnamed fields require `#[semval(variant = ...)]`",
+                )),
+            },
+            base_name.as_deref(),
+        )?);
+    }
+    Ok(validated)
+}
+
+fn variant_fields(variant_ident: &Ident, fields: &Fields) -> Result<Vec<Field>> {
+    let mut validated = Vec::with_capacity(fields.len());
+    for (index, field) in fields.iter().enumerate() {
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        if attrs.skip {
+            continue;
+        }
+        validated.push((index, field, attrs));
+    }
+    // A single validated field is wrapped into an invalidity
+    // variant of the same name
+    let single = validated.len() == 1;
+    validated
+        .into_iter()
+        .map(|(index, field, attrs)| {
+            let base_name = match &field.ident {
+                _ if single => variant_ident.to_string(),
+                Some(ident) => format!("{}{}", variant_ident, to_upper_camel_case(ident)),
+                None => format!("{variant_ident}{index}"),
+            };
+            let variant = format_ident!("{}", base_name);
+            new_field(
+                index,
+                field,
+                attrs,
+                |attrs| Ok(attrs.variant.clone().unwrap_or(variant)),
+                Some(&base_name),
+            )
+        })
+        .collect()
+}
+
+/// Fields with constraints are only validated recursively if
+/// explicitly requested.
+fn new_field(
+    index: usize,
+    field: &syn::Field,
+    attrs: FieldAttrs,
+    nested_variant: impl FnOnce(&FieldAttrs) -> Result<Ident>,
+    base_name: Option<&str>,
+) -> Result<Field> {
+    let nested = if attrs.nested || attrs.constraints.is_empty() {
+        Some(nested_variant(&attrs)?)
+    } else {
+        None
+    };
+    let constraints = attrs
+        .constraints
+        .into_iter()
+        .map(
+            |ConstraintAttr {
+                 constraint,
+                 variant,
+             }| {
+                let variant = match (variant, base_name) {
+                    (Some(variant), _) => variant,
+                    (None, Some(base_name)) => {
+                        format_ident!("{}{}", base_name, constraint.variant_suffix())
+                    }
+                    (None, None) => {
+                        return Err(Error::new_spanned(
+                            field,
+                            "constraints on unnamed fields require a `variant = ...` parameter",
+                        ));
+                    }
+                };
+                Ok(FieldConstraint {
+                    constraint,
+                    variant,
+                })
+            },
+        )
+        .collect::<Result<Vec<_>>>()?;
+    let field = Field {
+        member: member(index, field.ident.as_ref()),
+        binding: format_ident!("field_{}", index),
+        ty: field.ty.clone(),
+        nested,
+        skip_from: attrs.skip_from,
+        constraints,
+    };
+    if field.option_type().is_none()
+        && field
+            .constraints
+            .iter()
+            .any(|c| matches!(c.constraint, Constraint::Required))
+    {
+        return Err(Error::new_spanned(
+            &field.ty,
+            "`required` is only supported for fields of type `Option`",
+        ));
+    }
+    Ok(field)
+}
+
+fn mentions_any_ident(tokens: TokenStream, idents: &[&Ident]) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => idents.contains(&&ident),
+        TokenTree::Group(group) => mentions_any_ident(group.stream(), idents),
+        TokenTree::Punct(_) | TokenTree::Literal(_) => false,
+    })
+}
+
+fn member(index: usize, ident: Option<&Ident>) -> Member {
+    ident.map_or_else(|| Member::from(index), |ident| Member::Named(ident.clone()))
+}
+
+/// Converts a `snake_case` identifier into `UpperCamelCase`
+pub(crate) fn to_upper_camel_case(ident: &Ident) -> String {
+    let ident = ident.to_string();
+    let ident = ident.strip_prefix("r#").unwrap_or(&ident);
+    ident
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars).collect::<String>()
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,123 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Code generation for `#[derive(NestedInvalidity)]`
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    Attribute, Data, DeriveInput, Error, Fields, Member, Result, Type, parse_quote,
+    spanned::Spanned as _,
+};
+
+use crate::attr::NestedAttrs;
+
+/// The source field of a `struct` or `enum` variant, if any
+///
+/// Explicitly marked with `#[semval(source)]` or implicitly the only
+/// field of a tuple struct or variant unless marked as `#[semval(leaf)]`.
+fn source_field<'a>(attrs: &[Attribute], fields: &'a Fields) -> Result<Option<(Member, &'a Type)>> {
+    let leaf = NestedAttrs::parse(attrs)?.leaf;
+    let mut source = None;
+    for (index, field) in fields.iter().enumerate() {
+        let attrs = NestedAttrs::parse(&field.attrs)?;
+        if attrs.leaf {
+            return Err(Error::new(
+                field.span(),
+                "`leaf` is only supported on types and variants",
+            ));
+        }
+        if !attrs.source {
+            continue;
+        }
+        if leaf || source.is_some() {
+            return Err(Error::new(field.span(), "unexpected `source` field"));
+        }
+        let member = field
+            .ident
+            .clone()
+            .map_or_else(|| Member::from(index), Member::Named);
+        source = Some((member, &field.ty));
+    }
+    if source.is_some() || leaf {
+        return Ok(source);
+    }
+    match fields {
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => Ok(unnamed
+            .unnamed
+            .first()
+            .map(|field| (Member::from(0), &field.ty))),
+        _ => Ok(None),
+    }
+}
+
+/// A match arm that returns the source of the matched `path`
+fn source_arm<'a>(
+    path: &TokenStream,
+    source: Option<(Member, &'a Type)>,
+    sources: &mut Vec<&'a Type>,
+) -> TokenStream {
+    let Some((member, ty)) = source else {
+        return quote!(#path { .. } => ::core::option::Option::None,);
+    };
+    sources.push(ty);
+    quote!(#path { #member: source, .. } => ::core::option::Option::Some(source),)
+}
+
+pub(crate) fn derive(input: &DeriveInput) -> Result<TokenStream> {
+    let ident = &input.ident;
+    let mut sources = Vec::new();
+    let arms = match &input.data {
+        Data::Struct(data) => {
+            let source = source_field(&input.attrs, &data.fields)?;
+            vec![source_arm(&quote!(Self), source, &mut sources)]
+        }
+        Data::Enum(_) if NestedAttrs::parse(&input.attrs)?.leaf => {
+            vec![quote!(_ => ::core::option::Option::None,)]
+        }
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| {
+                let variant_ident = &variant.ident;
+                let source = source_field(&variant.attrs, &variant.fields)?;
+                Ok(source_arm(
+                    &quote!(Self::#variant_ident),
+                    source,
+                    &mut sources,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?,
+        Data::Union(_) => {
+            return Err(Error::new_spanned(input, "unions are not supported"));
+        }
+    };
+    let body = if arms.is_empty() {
+        quote!(match *self {})
+    } else {
+        quote!(match self { #( #arms )* })
+    };
+
+    let mut generics = input.generics.clone();
+    if generics.type_params().next().is_some() {
+        let where_clause = generics.make_where_clause();
+        where_clause
+            .predicates
+            .push(parse_quote!(Self: ::semval::Invalidity));
+        for ty in sources {
+            where_clause
+                .predicates
+                .push(parse_quote!(#ty: ::semval::NestedInvalidity));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::semval::NestedInvalidity for #ident #ty_generics #where_clause {
+            fn source(&self) -> ::core::option::Option<&dyn ::semval::NestedInvalidity> {
+                #body
+            }
+        }
+    })
+}
@@ -0,0 +1,129 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Code generation for `#[derive(Validate)]`
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Member, Result, Type, WherePredicate, ext::IdentExt as _, parse_quote};
+
+use crate::{
+    constraint,
+    model::{Container, Field, Shape},
+};
+
+/// Statements that validate a field
+///
+/// The `accessor` must evaluate to a reference of the field.
+///
+/// Invalidities of named fields are recorded at the field name.
+fn validate_field(field: &Field, accessor: &TokenStream, invalidity: &Type) -> TokenStream {
+    let nested = field.nested.as_ref().map(|variant| {
+        quote! {
+            let context = context.validate_with(#accessor, <#invalidity>::#variant);
+        }
+    });
+    let constraints = constraint::check_field(field, accessor, invalidity);
+    match &field.member {
+        Member::Named(ident) => {
+            let name = ident.unraw().to_string();
+            quote! {
+                let context = context.scoped(#name, |context| {
+                    #nested
+                    #constraints
+                    context
+                });
+            }
+        }
+        Member::Unnamed(_) => quote! {
+            #nested
+            #constraints
+        },
+    }
+}
+
+pub(crate) fn derive(input: &DeriveInput) -> Result<TokenStream> {
+    let container = Container::from_input(input)?;
+    let invalidity = &container.invalidity_type();
+
+    let mut generics = container.generics.clone();
+    {
+        let where_clause = generics.make_where_clause();
+        for Field { ty, nested, .. } in container.fields() {
+            // Bounds on concrete types would prevent normalization
+            // of their associated invalidity types
+            if nested.is_none() || !container.is_generic_type(ty) {
+                continue;
+            }
+            let predicate: WherePredicate = parse_quote!(#ty: ::semval::Validate);
+            where_clause.predicates.push(predicate);
+        }
+        // The invalidity type must satisfy the `Any` bound, which
+        // implies this bound for all of its type parameters
+        let predicate: WherePredicate = parse_quote!(#invalidity: 'static);
+        where_clause.predicates.push(predicate);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &container.shape {
+        Shape::Struct(fields) => {
+            let validations = fields.iter().map(|field| {
+                let member = &field.member;
+                validate_field(field, &quote!(&self.#member), invalidity)
+            });
+            quote! {
+                #( #validations )*
+                context
+            }
+        }
+        Shape::Enum(variants) => {
+            let arms = variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                let bindings = variant.fields.iter().map(
+                    |Field {
+                         member, binding, ..
+                     }| quote!(#member: #binding),
+                );
+                let validations = variant.fields.iter().map(|field| {
+                    let binding = &field.binding;
+                    validate_field(field, &quote!(#binding), invalidity)
+                });
+                quote! {
+                    Self::#ident { #( #bindings, )* .. } => {
+                        #( #validations )*
+                        context
+                    }
+                }
+            });
+            quote! {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #( #arms )*
+                    _ => context,
+                }
+            }
+        }
+    };
+
+    let ident = container.ident;
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::semval::Validate for #ident #ty_generics #where_clause {
+            type Invalidity = #invalidity;
+
+            fn validate(&self) -> ::semval::ValidationResult<Self::Invalidity> {
+                self.validate_within(::semval::context::Context::new()).into()
+            }
+
+            fn validate_within<__S>(
+                &self,
+                context: ::semval::context::Context<Self::Invalidity, __S>,
+            ) -> ::semval::context::Context<Self::Invalidity, __S>
+            where
+                __S: ::semval::context::Storage<Self::Invalidity>,
+            {
+                #body
+            }
+        }
+    })
+}
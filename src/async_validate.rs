@@ -0,0 +1,413 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Asynchronous validation
+//!
+//! Executor-agnostic, the futures are neither required to be `Send`
+//! nor to be spawned.
+
+use core::future::Future;
+
+use futures_util::future::join_all;
+
+use crate::{
+    Invalidity, Validate, ValidationResult,
+    context::{Context, Storage},
+};
+
+/// A trait for validating types asynchronously
+///
+/// Needed for validations that require I/O, e.g. for checking if
+/// some referenced entity exists.
+///
+/// # Synchronous validations
+///
+/// Types that implement [`Validate`] don't implement `AsyncValidate`
+/// implicitly. A blanket implementation would overlap with the
+/// implementations for references, `Option`, slices, and `Vec` that
+/// forward to the elements, because these types also implement
+/// `Validate`. Overlapping implementations are rejected by the coherence
+/// rules, at least until specialization becomes available.
+///
+/// Wrap synchronous validations into [`Ready`] instead:
+///
+/// ```
+/// # use semval::{prelude::*, AsyncValidate, Ready};
+/// struct Name(&'static str);
+///
+/// impl Validate for Name {
+///     type Invalidity = ();
+///
+///     fn validate(&self) -> ValidationResult<Self::Invalidity> {
+///         ValidationContext::new().invalidate_if(self.0.is_empty(), ()).into()
+///     }
+/// }
+///
+/// # futures_executor::block_on(async {
+/// let context = ValidationContext::<()>::new()
+///     .validate_async(&Ready(Name("")))
+///     .await
+///     .validate_async(&vec![Ready(Name("a")), Ready(Name(""))])
+///     .await;
+/// assert_eq!(2, context.len());
+/// # });
+/// ```
+pub trait AsyncValidate {
+    /// Invalidity objectives
+    type Invalidity: Invalidity;
+
+    /// Perform the validation asynchronously
+    fn validate_async(&self) -> impl Future<Output = ValidationResult<Self::Invalidity>>;
+
+    /// Perform the validation asynchronously within an existing context
+    ///
+    /// The asynchronous counterpart of [`Validate::validate_within()`].
+    /// The default implementation skips the validation if the context
+    /// is already done.
+    fn validate_async_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> impl Future<Output = Context<Self::Invalidity, S>>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        async move {
+            if context.is_done() {
+                return context;
+            }
+            context.merge_result(self.validate_async().await)
+        }
+    }
+}
+
+/// Validates a type that implements `Validate` asynchronously
+///
+/// The validation is performed synchronously when awaiting the
+/// future for the first time.
+#[derive(Debug, Clone, Copy)]
+pub struct Ready<T>(pub T);
+
+impl<T> AsyncValidate for Ready<T>
+where
+    T: Validate,
+{
+    type Invalidity = T::Invalidity;
+
+    async fn validate_async(&self) -> ValidationResult<Self::Invalidity> {
+        self.0.validate()
+    }
+
+    async fn validate_async_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        self.0.validate_within(context)
+    }
+}
+
+/// `AsyncValidate` is implemented for any reference of a type
+/// that implements `AsyncValidate`.
+impl<V> AsyncValidate for &V
+where
+    V: AsyncValidate + ?Sized,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate_async(&self) -> impl Future<Output = ValidationResult<Self::Invalidity>> {
+        (*self).validate_async()
+    }
+
+    fn validate_async_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> impl Future<Output = Context<Self::Invalidity, S>>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        (*self).validate_async_within(context)
+    }
+}
+
+/// Validate `Some` or otherwise implicitly evaluate to `Ok`
+/// in case of `None`
+impl<V> AsyncValidate for Option<V>
+where
+    V: AsyncValidate,
+{
+    type Invalidity = V::Invalidity;
+
+    async fn validate_async(&self) -> ValidationResult<Self::Invalidity> {
+        if let Some(some) = self {
+            some.validate_async().await
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn validate_async_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        if let Some(some) = self {
+            some.validate_async_within(context).await
+        } else {
+            context
+        }
+    }
+}
+
+/// Validate all elements of a slice concurrently
+///
+/// The invalidities of each element are recorded at its index.
+/// Fail-fast contexts validate the elements one after another
+/// instead and stop after the first element that failed.
+impl<V> AsyncValidate for [V]
+where
+    V: AsyncValidate,
+{
+    type Invalidity = V::Invalidity;
+
+    async fn validate_async(&self) -> ValidationResult<Self::Invalidity> {
+        self.validate_async_within(Context::new()).await.into()
+    }
+
+    async fn validate_async_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        if context.is_fail_fast() {
+            let mut context = context;
+            for (index, element) in self.iter().enumerate() {
+                if context.is_done() {
+                    break;
+                }
+                let validated = element
+                    .validate_async_within(context.nested::<_, S>())
+                    .await;
+                context = context.merge_mapped_at(index, validated, core::convert::identity);
+            }
+            return context;
+        }
+        join_all(
+            self.iter()
+                .map(|element| element.validate_async_within(context.nested::<_, S>())),
+        )
+        .await
+        .into_iter()
+        .enumerate()
+        .fold(context, |context, (index, validated)| {
+            context.merge_mapped_at(index, validated, core::convert::identity)
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V> AsyncValidate for alloc::vec::Vec<V>
+where
+    V: AsyncValidate,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate_async(&self) -> impl Future<Output = ValidationResult<Self::Invalidity>> {
+        self.as_slice().validate_async()
+    }
+
+    fn validate_async_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> impl Future<Output = Context<Self::Invalidity, S>>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        self.as_slice().validate_async_within(context)
+    }
+}
+
+impl<V, S> Context<V, S>
+where
+    V: Invalidity,
+    S: Storage<V>,
+{
+    /// Validate the target asynchronously and merge the result into this context
+    pub async fn validate_async<U>(self, target: &impl AsyncValidate<Invalidity = U>) -> Self
+    where
+        U: Invalidity + Into<V>,
+    {
+        self.validate_with_async(target, Into::into).await
+    }
+
+    /// Validate the target asynchronously and merge the mapped result into this context
+    pub async fn validate_with_async<F, U>(
+        self,
+        target: &impl AsyncValidate<Invalidity = U>,
+        map: F,
+    ) -> Self
+    where
+        F: Fn(U) -> V,
+        U: Invalidity,
+    {
+        if self.is_done() {
+            return self;
+        }
+        let validated = target
+            .validate_async_within(self.nested::<U, S::Nested<U>>())
+            .await;
+        self.merge_mapped(validated, |entry| entry.map(&map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{rc::Rc, vec, vec::Vec};
+    use core::{
+        cell::RefCell,
+        pin::Pin,
+        task::{Context as TaskContext, Poll},
+    };
+
+    use futures_executor::block_on;
+
+    use crate::{
+        context::{Entry, InlineContext},
+        path::Path,
+    };
+
+    /// Yields once before completing to interleave concurrent futures
+    #[derive(Default)]
+    struct YieldNow(bool);
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    struct Registered {
+        email: &'static str,
+        log: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl AsyncValidate for Registered {
+        type Invalidity = &'static str;
+
+        async fn validate_async(&self) -> ValidationResult<Self::Invalidity> {
+            self.log.borrow_mut().push(self.email);
+            YieldNow::default().await;
+            self.log.borrow_mut().push(self.email);
+            Context::new()
+                .invalidate_if(self.email.is_empty(), "empty")
+                .into()
+        }
+    }
+
+    struct Dummy(bool);
+
+    impl Validate for Dummy {
+        type Invalidity = ();
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            Context::new().invalidate_if(!self.0, ()).into()
+        }
+    }
+
+    #[test]
+    fn ready() {
+        assert!(block_on(Ready(Dummy(true)).validate_async()).is_ok());
+        assert!(block_on(Ready(Dummy(false)).validate_async()).is_err());
+        assert!(block_on(Ready(&[Dummy(true), Dummy(false)] as &[_]).validate_async()).is_err());
+    }
+
+    #[test]
+    fn option() {
+        assert!(block_on(None::<Ready<Dummy>>.validate_async()).is_ok());
+        assert!(block_on(Some(Ready(Dummy(false))).validate_async()).is_err());
+    }
+
+    #[test]
+    fn slice_concurrently() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let elements = ["a", "", "b"].map(|email| Registered {
+            email,
+            log: Rc::clone(&log),
+        });
+        let context = block_on(elements.as_slice().validate_async()).unwrap_err();
+        assert_eq!(
+            vec![Entry::error(Path::from_iter([1]), "empty")],
+            context.into_iter().collect::<Vec<_>>()
+        );
+        // All validations have been started before any of them finished
+        assert_eq!(vec!["a", "", "b", "a", "", "b"], *log.borrow());
+    }
+
+    #[test]
+    fn slice_fail_fast() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let elements = ["a", "", "b"].map(|email| Registered {
+            email,
+            log: Rc::clone(&log),
+        });
+        let context = block_on(
+            elements
+                .as_slice()
+                .validate_async_within(Context::<_>::fail_fast()),
+        );
+        assert_eq!(
+            vec![Entry::error(Path::from_iter([1]), "empty")],
+            context.into_iter().collect::<Vec<_>>()
+        );
+        // The remaining elements have not been validated after the first error
+        assert_eq!(vec!["a", "a", "", ""], *log.borrow());
+    }
+
+    #[test]
+    fn slice_bounded() {
+        let elements = ["", "a", "", ""].map(|email| Registered {
+            email,
+            log: Rc::default(),
+        });
+        let context =
+            block_on(InlineContext::<_, 1>::bounded(1).validate_async(&elements.as_slice()));
+        assert_eq!(
+            vec![Entry::error(Path::from_iter([0]), "empty")],
+            context.iter().cloned().collect::<Vec<_>>()
+        );
+        assert_eq!(2, context.omitted());
+    }
+
+    #[test]
+    fn context() {
+        let context = block_on(async {
+            Context::<Option<()>>::new()
+                .validate_async(&Ready(Dummy(false)))
+                .await
+                .validate_with_async(&Ready(Dummy(false)), |()| None)
+                .await
+        });
+        assert_eq!(
+            vec![Some(()), None],
+            context
+                .into_iter()
+                .map(|entry| entry.invalidity)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,387 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Implementations of `Validate` for collections and smart pointers
+//!
+//! Only requires a global allocator, but not `std`.
+
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    rc::Rc,
+    vec::Vec,
+};
+
+use crate::{
+    Validate, ValidationResult,
+    context::{Context, Storage},
+    map::{KeySegment, MapKey},
+    validate_elements_within,
+};
+
+impl<V> Validate for Vec<V>
+where
+    V: Validate,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate(&self) -> ValidationResult<Self::Invalidity> {
+        self.as_slice().validate()
+    }
+
+    fn validate_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        self.as_slice().validate_within(context)
+    }
+}
+
+/// Validate all elements in order
+///
+/// The invalidities of each element are recorded at its index.
+impl<V> Validate for VecDeque<V>
+where
+    V: Validate,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate(&self) -> ValidationResult<Self::Invalidity> {
+        self.validate_within(Context::new()).into()
+    }
+
+    /// Stops at the first invalid element in fail-fast mode
+    fn validate_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        validate_elements_within(context, self.iter().enumerate())
+    }
+}
+
+/// Validate all elements in order
+///
+/// The invalidities of each element are recorded at its index
+/// in the ordered set.
+impl<V> Validate for BTreeSet<V>
+where
+    V: Validate,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate(&self) -> ValidationResult<Self::Invalidity> {
+        self.validate_within(Context::new()).into()
+    }
+
+    /// Stops at the first invalid element in fail-fast mode
+    fn validate_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        validate_elements_within(context, self.iter().enumerate())
+    }
+}
+
+/// Validate all values in the order of their keys
+///
+/// The invalidities of each value are recorded at its key. Keys
+/// are not validated.
+impl<K, V> Validate for BTreeMap<K, V>
+where
+    K: MapKey,
+    V: Validate,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate(&self) -> ValidationResult<Self::Invalidity> {
+        self.validate_within(Context::new()).into()
+    }
+
+    /// Stops at the first invalid value in fail-fast mode
+    fn validate_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        validate_elements_within(
+            context,
+            self.iter().map(|(key, value)| (KeySegment(key), value)),
+        )
+    }
+}
+
+/// Validate all values
+///
+/// The invalidities of each value are recorded at its key. Keys
+/// are not validated.
+///
+/// The iteration order is unspecified. In fail-fast mode the
+/// reported invalid value is not deterministic if multiple
+/// values are invalid.
+#[cfg(feature = "std")]
+impl<K, V, H> Validate for std::collections::HashMap<K, V, H>
+where
+    K: MapKey,
+    V: Validate,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate(&self) -> ValidationResult<Self::Invalidity> {
+        self.validate_within(Context::new()).into()
+    }
+
+    /// Stops at the first invalid value in fail-fast mode
+    fn validate_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        validate_elements_within(
+            context,
+            self.iter().map(|(key, value)| (KeySegment(key), value)),
+        )
+    }
+}
+
+/// Validate all elements
+///
+/// The invalidities of the elements are not recorded at an index,
+/// because the iteration order is unspecified.
+#[cfg(feature = "std")]
+impl<V, H> Validate for std::collections::HashSet<V, H>
+where
+    V: Validate,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate(&self) -> ValidationResult<Self::Invalidity> {
+        self.validate_within(Context::new()).into()
+    }
+
+    /// Stops at the first invalid element in fail-fast mode
+    fn validate_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        self.iter().fold(context, Context::validate)
+    }
+}
+
+impl<'a, V> Validate for Cow<'a, V>
+where
+    V: Validate + ToOwned + 'a + ?Sized,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate(&self) -> ValidationResult<Self::Invalidity> {
+        self.as_ref().validate()
+    }
+
+    fn validate_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        self.as_ref().validate_within(context)
+    }
+}
+
+impl<V> Validate for Box<V>
+where
+    V: Validate + ?Sized,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate(&self) -> ValidationResult<Self::Invalidity> {
+        self.as_ref().validate()
+    }
+
+    fn validate_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        self.as_ref().validate_within(context)
+    }
+}
+
+impl<V> Validate for Rc<V>
+where
+    V: Validate + ?Sized,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate(&self) -> ValidationResult<Self::Invalidity> {
+        self.as_ref().validate()
+    }
+
+    fn validate_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        self.as_ref().validate_within(context)
+    }
+}
+
+/// Only available on targets with atomic pointers
+#[cfg(target_has_atomic = "ptr")]
+impl<V> Validate for alloc::sync::Arc<V>
+where
+    V: Validate + ?Sized,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate(&self) -> ValidationResult<Self::Invalidity> {
+        self.as_ref().validate()
+    }
+
+    fn validate_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        self.as_ref().validate_within(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{
+        string::{String, ToString as _},
+        vec,
+    };
+
+    use crate::{
+        IsValid as _,
+        context::Context,
+        path::{Path, PathSegment},
+    };
+
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    struct Name(&'static str);
+
+    impl Validate for Name {
+        type Invalidity = ();
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            Context::new().invalidate_if(self.0.is_empty(), ()).into()
+        }
+    }
+
+    fn paths(result: ValidationResult<()>) -> Vec<String> {
+        result
+            .err()
+            .map(|context| {
+                context
+                    .into_iter()
+                    .map(|entry| entry.path.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn smart_pointers() {
+        assert!(Box::new(Name("a")).is_valid());
+        assert!(!Rc::new(Name("")).is_valid());
+        assert!(!alloc::sync::Arc::new(Name("")).is_valid());
+        let boxed: Box<[Name]> = Box::new([Name("a"), Name("")]);
+        assert_eq!(vec!["[1]"], paths(boxed.validate()));
+        assert_eq!(1, boxed.count_invalidities());
+        let components: Vec<Box<dyn crate::DynValidate>> =
+            vec![Box::new(Name("a")), Box::new(Rc::new(Name("")))];
+        assert_eq!(1, components.validate().unwrap_err().len());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_map() {
+        let names = std::collections::HashMap::from([(1, Name("")), (2, Name("b"))]);
+        assert_eq!(vec!["[\"1\"]"], paths(names.validate()));
+        assert!(!names.is_valid());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_set() {
+        let names = std::collections::HashSet::from([Name(""), Name("a")]);
+        assert_eq!(vec![""], paths(names.validate()));
+        assert_eq!(1, names.count_invalidities());
+    }
+
+    #[test]
+    fn vec_deque() {
+        let mut names = VecDeque::from([Name(""), Name("b")]);
+        names.push_front(Name("a"));
+        assert_eq!(vec!["[1]"], paths(names.validate()));
+    }
+
+    #[test]
+    fn btree_set() {
+        let names = BTreeSet::from([Name("b"), Name(""), Name("a")]);
+        assert_eq!(vec!["[0]"], paths(names.validate()));
+    }
+
+    #[test]
+    fn btree_map() {
+        let names = BTreeMap::from([("x", Name("")), ("y", Name("b")), ("z", Name(""))]);
+        assert_eq!(vec![r#"["x"]"#, r#"["z"]"#], paths(names.validate()));
+        assert_eq!(2, names.count_invalidities());
+        assert_eq!(
+            vec![Path::from_iter([PathSegment::key("x")])],
+            names
+                .validate_fail_fast()
+                .unwrap_err()
+                .into_iter()
+                .map(|entry| entry.path)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    /// A key that doesn't implement `Display`
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Coordinates(u8, u8);
+
+    impl MapKey for Coordinates {
+        fn to_path_segment(&self) -> PathSegment {
+            PathSegment::key(alloc::format!("{},{}", self.0, self.1))
+        }
+    }
+
+    #[test]
+    fn btree_map_without_display() {
+        let names = BTreeMap::from([
+            (Coordinates(0, 1), Name("")),
+            (Coordinates(1, 0), Name("a")),
+        ]);
+        assert_eq!(vec![r#"["0,1"]"#], paths(names.validate()));
+    }
+}
@@ -0,0 +1,257 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Common constraints for leaf validations
+//!
+//! Each constraint carries its parameters and could be recorded
+//! as an invalidity when it has been violated. The derive macro
+//! `Validate` uses these types for the invalidity variants of
+//! `#[semval(...)]` constraint attributes.
+
+/// Number of elements or characters
+///
+/// Used for checking the `Length` and `NonEmpty` constraints.
+pub trait HasLength {
+    /// The length
+    fn length(&self) -> usize;
+}
+
+impl<T> HasLength for &T
+where
+    T: HasLength + ?Sized,
+{
+    fn length(&self) -> usize {
+        (*self).length()
+    }
+}
+
+/// The number of characters, not bytes
+impl HasLength for str {
+    fn length(&self) -> usize {
+        self.chars().count()
+    }
+}
+
+impl<T> HasLength for [T] {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T, const N: usize> HasLength for [T; N] {
+    fn length(&self) -> usize {
+        N
+    }
+}
+
+/// The number of characters, not bytes
+#[cfg(feature = "alloc")]
+impl HasLength for alloc::string::String {
+    fn length(&self) -> usize {
+        self.as_str().length()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl HasLength for alloc::borrow::Cow<'_, str> {
+    fn length(&self) -> usize {
+        self.as_ref().length()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> HasLength for alloc::vec::Vec<T> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> HasLength for alloc::collections::VecDeque<T> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V> HasLength for alloc::collections::BTreeMap<K, V> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> HasLength for alloc::collections::BTreeSet<T> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> HasLength for std::collections::HashMap<K, V, S> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S> HasLength for std::collections::HashSet<T, S> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Lower and upper bounds for the length, both inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Length {
+    /// Minimum length
+    pub min: Option<usize>,
+
+    /// Maximum length
+    pub max: Option<usize>,
+}
+
+impl Length {
+    /// Check if the length of a value satisfies this constraint
+    #[must_use]
+    pub fn is_satisfied_by(&self, value: &(impl HasLength + ?Sized)) -> bool {
+        let length = value.length();
+        self.min.is_none_or(|min| length >= min) && self.max.is_none_or(|max| length <= max)
+    }
+}
+
+/// Lower and upper bounds for a value, both inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Range<T> {
+    /// Minimum value
+    pub min: Option<T>,
+
+    /// Maximum value
+    pub max: Option<T>,
+}
+
+impl<T> Range<T>
+where
+    T: PartialOrd,
+{
+    /// Check if a value satisfies this constraint
+    #[must_use]
+    pub fn is_satisfied_by(&self, value: &T) -> bool {
+        self.min.as_ref().is_none_or(|min| value >= min)
+            && self.max.as_ref().is_none_or(|max| value <= max)
+    }
+}
+
+/// The value must not be empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NonEmpty;
+
+impl NonEmpty {
+    /// Check if a value satisfies this constraint
+    #[must_use]
+    pub fn is_satisfied_by(&self, value: &(impl HasLength + ?Sized)) -> bool {
+        value.length() > 0
+    }
+}
+
+/// The value must match a regular expression
+///
+/// Derived `pattern` constraints require the `regex` feature.
+///
+#[cfg_attr(all(feature = "derive", feature = "regex"), doc = "```")]
+#[cfg_attr(
+    not(all(feature = "derive", feature = "regex")),
+    doc = "```compile_fail"
+)]
+/// # use semval::prelude::*;
+/// #[derive(Validate, semval::Invalidity)]
+/// struct Email {
+///     #[semval(pattern = "^[^@]+@[^@]+$")]
+///     address: String,
+/// }
+/// ```
+///
+/// The regular expressions of derived `pattern` constraints are
+/// checked at compile time.
+///
+/// ```compile_fail
+/// # use semval::prelude::*;
+/// #[derive(Validate, semval::Invalidity)]
+/// struct Email {
+///     #[semval(pattern = "[a-")]
+///     address: String,
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pattern {
+    /// The regular expression
+    pub regex: &'static str,
+}
+
+/// An optional value must be present
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Required;
+
+impl Required {
+    /// Check if an optional value satisfies this constraint
+    #[must_use]
+    pub const fn is_satisfied_by<T>(&self, value: &Option<T>) -> bool {
+        value.is_some()
+    }
+}
+
+/// A custom predicate must be satisfied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Custom {
+    /// The name of the predicate function
+    pub function: &'static str,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn length() {
+        let constraint = Length {
+            min: Some(2),
+            max: Some(3),
+        };
+        assert!(!constraint.is_satisfied_by("a"));
+        assert!(constraint.is_satisfied_by("ab"));
+        assert!(constraint.is_satisfied_by("äöü"));
+        assert!(!constraint.is_satisfied_by("abcd"));
+        assert!(constraint.is_satisfied_by(&[1, 2]));
+        assert!(
+            Length {
+                min: None,
+                max: None
+            }
+            .is_satisfied_by("")
+        );
+    }
+
+    #[test]
+    fn range() {
+        let constraint = Range {
+            min: Some(1),
+            max: None,
+        };
+        assert!(!constraint.is_satisfied_by(&0));
+        assert!(constraint.is_satisfied_by(&1));
+        assert!(constraint.is_satisfied_by(&usize::MAX));
+    }
+
+    #[test]
+    fn non_empty() {
+        assert!(!NonEmpty.is_satisfied_by(""));
+        assert!(NonEmpty.is_satisfied_by(" "));
+        assert!(!NonEmpty.is_satisfied_by(&[] as &[()]));
+    }
+
+    #[test]
+    fn required() {
+        assert!(!Required.is_satisfied_by(&None::<()>));
+        assert!(Required.is_satisfied_by(&Some(())));
+    }
+}
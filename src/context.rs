@@ -1,57 +1,293 @@
 // SPDX-FileCopyrightText: slowtec GmbH
 // SPDX-License-Identifier: MPL-2.0
 
-use core::iter::once;
+use core::{convert::identity, fmt, iter::once, marker::PhantomData};
 
 use crate::{
-    Invalidity, Validate, ValidationResult,
+    Invalidity, Validate,
+    path::{Path, PathSegment},
     smallvec::SmallVec,
-    util::{IsEmpty, Mergeable, MergeableSized},
+    util::{IsEmpty, Mergeable, UnitResult},
 };
 
 const SMALLVEC_ARRAY_LEN: usize = 8;
 
-type SmallVecArray<V> = [V; SMALLVEC_ARRAY_LEN];
+/// A storage with an inline capacity of `N` invalidities
+///
+/// Stores up to `N` invalidities inline before allocating memory
+/// on the heap.
+pub type InlineStorage<V, const N: usize> = SmallVec<[Entry<V>; N]>;
+
+/// The default storage of a [`Context`]
+///
+/// Stores up to 8 invalidities inline.
+pub type DefaultStorage<V> = InlineStorage<V, SMALLVEC_ARRAY_LEN>;
+
+/// A context with an inline capacity of `N` invalidities
+///
+/// Small capacities reduce the memory footprint of leaf types,
+/// large capacities avoid allocations for aggregates that
+/// commonly collect many invalidities. Targets that are validated
+/// [within](Validate::validate_within) the context record their
+/// invalidities directly into its storage. Contexts of different
+/// capacities could be merged and converted into each other.
+pub type InlineContext<V, const N: usize> = Context<V, InlineStorage<V, N>>;
+
+/// A storage with a fixed capacity of `N` invalidities
+///
+/// Never allocates memory on the heap.
+#[cfg(feature = "heapless")]
+pub type FixedStorage<V, const N: usize> = heapless::Vec<Entry<V>, N>;
+
+/// A context with a fixed capacity of `N` invalidities
+///
+/// The context is [bounded](Context::bounded) by its capacity and
+/// only counts all invalidities that exceed it as
+/// [omitted](Context::omitted).
+///
+/// The storage never allocates memory on the heap. Targets that
+/// implement [`Validate::validate_within()`] record their invalidities
+/// in fixed storages of the same capacity. Validations don't allocate,
+/// except for:
+///
+/// - Paths with more than [`INLINE_PATH_LEN`](crate::path::INLINE_PATH_LEN)
+///   segments.
+/// - Paths that contain the keys of maps.
+/// - Targets that only implement [`Validate::validate()`] and collect
+///   more invalidities than the inline capacity of the [`DefaultStorage`].
+#[cfg(feature = "heapless")]
+pub type FixedContext<V, const N: usize> = Context<V, FixedStorage<V, N>>;
+
+/// The storage of the invalidities in a [`Context`]
+///
+/// An accumulator of [entries](Entry) that could be iterated by
+/// reference and consumed by iterating over them. Implemented
+/// for `Vec`, [`SmallVec`] and `heapless::Vec`.
+pub trait Storage<V>: Mergeable<Item = Entry<V>> + IsEmpty + IntoIterator<Item = Entry<V>> {
+    /// The storage of nested validations
+    ///
+    /// Components are validated within a context of this storage,
+    /// e.g. by [`Context::validate()`], before their invalidities
+    /// are merged.
+    type Nested<U>: Storage<U>;
+
+    /// Iterate over the stored entries
+    fn entries<'a>(&'a self) -> impl ExactSizeIterator<Item = &'a Entry<V>>
+    where
+        V: 'a;
+}
+
+impl<V, const N: usize> Storage<V> for InlineStorage<V, N> {
+    type Nested<U> = InlineStorage<U, N>;
+
+    fn entries<'a>(&'a self) -> impl ExactSizeIterator<Item = &'a Entry<V>>
+    where
+        V: 'a,
+    {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V> Storage<V> for alloc::vec::Vec<Entry<V>> {
+    type Nested<U> = alloc::vec::Vec<Entry<U>>;
+
+    fn entries<'a>(&'a self) -> impl ExactSizeIterator<Item = &'a Entry<V>>
+    where
+        V: 'a,
+    {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<V, const N: usize> Storage<V> for FixedStorage<V, N> {
+    type Nested<U> = FixedStorage<U, N>;
+
+    fn entries<'a>(&'a self) -> impl ExactSizeIterator<Item = &'a Entry<V>>
+    where
+        V: 'a,
+    {
+        self.iter()
+    }
+}
+
+/// The severity of an invalidity
+///
+/// Only errors cause a validation to fail. Warnings and infos
+/// are reported alongside a successful validation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Severity {
+    /// The value is invalid and must be rejected
+    #[default]
+    Error,
+
+    /// The value is valid but probably not as intended
+    Warning,
+
+    /// The value is valid, just for information
+    Info,
+}
+
+impl Severity {
+    /// Check if this is [`Severity::Error`]
+    #[must_use]
+    pub const fn is_error(&self) -> bool {
+        matches!(self, Self::Error)
+    }
+}
+
+/// An invalidity that has been recorded in a [`Context`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub struct Entry<V> {
+    /// The location relative to the validated value
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub path: Path,
+
+    /// The severity
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub severity: Severity,
+
+    /// The invalidity
+    pub invalidity: V,
+}
+
+impl<V> Entry<V> {
+    /// An error at the given path
+    #[must_use]
+    pub const fn error(path: Path, invalidity: V) -> Self {
+        Self {
+            path,
+            severity: Severity::Error,
+            invalidity,
+        }
+    }
+
+    /// Check if the severity is [`Severity::Error`]
+    #[must_use]
+    pub const fn is_error(&self) -> bool {
+        self.severity.is_error()
+    }
+
+    /// Map the invalidity
+    #[must_use]
+    pub fn map<U>(self, map: impl FnOnce(V) -> U) -> Entry<U> {
+        let Self {
+            path,
+            severity,
+            invalidity,
+        } = self;
+        Entry {
+            path,
+            severity,
+            invalidity: map(invalidity),
+        }
+    }
+}
+
+/// Prepend a segment to the paths of entries
+///
+/// The segment is only converted when prepending it to the
+/// first entry.
+fn prepend_lazily<V>(segment: impl Into<PathSegment>) -> impl FnMut(Entry<V>) -> Entry<V> {
+    let mut pending = Some(segment);
+    let mut prefix = None;
+    move |mut entry| {
+        if let Some(segment) = pending.take() {
+            prefix = Some(segment.into());
+        }
+        if let Some(prefix) = &prefix {
+            entry.path.prepend(prefix.clone());
+        }
+        entry
+    }
+}
 
 /// A collection of invalidities resulting from a validation
 ///
 /// Collects invalidities that are detected while performing
-/// a validation.
-#[derive(Clone, Debug, Default)]
-#[cfg_attr(test, derive(Eq, PartialEq))]
-pub struct Context<V>
+/// a validation. Each invalidity is recorded together with the
+/// [`Path`] of the component that has been considered invalid,
+/// relative to the validated value.
+///
+/// A context either collects all invalidities or, in _fail-fast_ mode,
+/// stops recording and skips all further validations after the first
+/// invalidity has been detected.
+///
+/// The number of stored invalidities could be [bounded](Self::bounded).
+/// Invalidities that exceed the limit are only counted.
+///
+/// Each invalidity has a [`Severity`]. Only errors render a context
+/// invalid, but all invalidities are propagated to the caller.
+///
+/// The invalidities are stored in a [`Storage`] that could be
+/// replaced by a custom type.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Context<V, S = DefaultStorage<V>>
 where
     V: Invalidity,
 {
-    invalidities: SmallVec<SmallVecArray<V>>,
+    invalidities: S,
+    fail_fast: bool,
+    limit: Option<usize>,
+    omitted: usize,
+    omitted_errors: usize,
+    invalidity: PhantomData<V>,
 }
 
-impl<V> IsEmpty for Context<V>
+impl<V, S> IsEmpty for Context<V, S>
 where
     V: Invalidity,
+    S: Storage<V>,
 {
     fn is_empty(&self) -> bool {
-        self.invalidities.is_empty()
+        self.invalidities.is_empty() && self.omitted.is_empty()
     }
 }
 
-impl<V> Mergeable for Context<V>
+impl<V, S> Mergeable for Context<V, S>
 where
     V: Invalidity,
+    S: Storage<V>,
 {
-    type Item = V;
+    type Item = Entry<V>;
 
     fn empty<H>(capacity_hint: H) -> Self
     where
         H: Into<Option<usize>>,
     {
         let invalidities = Mergeable::empty(capacity_hint);
-        Self { invalidities }
+        Self {
+            invalidities,
+            fail_fast: false,
+            limit: None,
+            omitted: Mergeable::empty(None),
+            omitted_errors: Mergeable::empty(None),
+            invalidity: PhantomData,
+        }
     }
 
     fn merge(mut self, other: Self) -> Self {
-        self.invalidities = self.invalidities.merge(other.invalidities);
-        self
+        if self.is_done() {
+            return self;
+        }
+        let Self {
+            invalidities,
+            omitted,
+            omitted_errors,
+            ..
+        } = other;
+        self.omitted = self.omitted.merge(omitted);
+        self.omitted_errors = self.omitted_errors.merge(omitted_errors);
+        if self.limit().is_none() {
+            self.invalidities = self.invalidities.merge(invalidities);
+            return self;
+        }
+        let count = invalidities.entries().len();
+        self.merge_iter(count, invalidities.into_iter())
     }
 
     fn merge_iter<H, I>(mut self, count_hint: H, iter: I) -> Self
@@ -59,36 +295,491 @@ where
         H: Into<Option<usize>>,
         I: Iterator<Item = Self::Item>,
     {
-        self.invalidities = self.invalidities.merge_iter(count_hint, iter);
+        if self.is_done() {
+            return self;
+        }
+        let Some(limit) = self.limit() else {
+            self.invalidities = self.invalidities.merge_iter(count_hint, iter);
+            return self;
+        };
+        let capacity = limit.saturating_sub(self.iter().len());
+        let mut iter = iter;
+        self.invalidities = self.invalidities.merge_iter(
+            count_hint.into().map(|count| count.min(capacity)),
+            iter.by_ref().take(capacity),
+        );
+        self.omit_entries(iter);
         self
     }
-}
 
-impl<V> MergeableSized for Context<V> where V: Invalidity {}
+    fn capacity_limit(&self) -> Option<usize> {
+        self.limit()
+    }
+}
 
 impl<V> Context<V>
 where
     V: Invalidity,
 {
     /// Create a new valid and empty context
+    ///
+    /// Contexts with a custom [`Storage`] are created with
+    /// [`Default::default()`].
     #[inline]
     #[must_use]
     pub fn new() -> Self {
-        Self::empty(<SmallVecArray<V> as smallvec::Array>::size())
+        Self::default()
+    }
+}
+
+/// A new valid and empty context
+impl<V, S> Default for Context<V, S>
+where
+    V: Invalidity,
+    S: Storage<V>,
+{
+    fn default() -> Self {
+        Self::empty(None)
+    }
+}
+
+impl<V, S> Context<V, S>
+where
+    V: Invalidity,
+    S: Storage<V>,
+{
+    /// Create a new valid and empty context in fail-fast mode
+    ///
+    /// Once invalid, the context ignores all further invalidities
+    /// and skips all subsequent validations. Targets are validated
+    /// [within](Validate::validate_within) a fail-fast context.
+    #[inline]
+    #[must_use]
+    pub fn fail_fast() -> Self {
+        Self {
+            fail_fast: true,
+            ..Self::default()
+        }
+    }
+
+    /// Create a new valid and empty context that stores at most
+    /// `limit` invalidities
+    ///
+    /// All subsequent invalidities are counted as [omitted](Self::omitted).
+    /// Targets are validated [within](Validate::validate_within) a
+    /// context that is bounded by the remaining capacity.
+    #[inline]
+    #[must_use]
+    pub fn bounded(limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..Self::default()
+        }
+    }
+
+    /// Create a new valid and empty context that only counts invalidities
+    ///
+    /// Equivalent to a context [bounded](Self::bounded) by 0 that omits
+    /// all invalidities.
+    #[inline]
+    #[must_use]
+    pub fn counting() -> Self {
+        Self::bounded(0)
+    }
+
+    /// Create a new valid and empty context that only checks the validity
+    ///
+    /// Combines the [fail-fast](Self::fail_fast) and [counting](Self::counting)
+    /// modes, i.e. neither stores any invalidities nor performs any
+    /// further validations after the first error. Used by
+    /// [`IsValid::is_valid()`](crate::IsValid::is_valid).
+    #[inline]
+    #[must_use]
+    pub fn checking() -> Self {
+        Self {
+            fail_fast: true,
+            ..Self::counting()
+        }
+    }
+
+    /// Check if the context only counts invalidities
+    #[inline]
+    #[must_use]
+    pub const fn is_counting(&self) -> bool {
+        matches!(self.limit, Some(0))
+    }
+
+    /// The maximum number of stored invalidities, if bounded
+    ///
+    /// Includes the capacity of the storage if it is limited.
+    #[must_use]
+    pub fn limit(&self) -> Option<usize> {
+        match (self.limit, self.invalidities.capacity_limit()) {
+            (Some(limit), Some(capacity)) => Some(limit.min(capacity)),
+            (limit, capacity) => limit.or(capacity),
+        }
+    }
+
+    /// The number of invalidities that have been omitted
+    /// after exceeding the limit
+    ///
+    /// Omitted invalidities are not included when iterating
+    /// over the context.
+    #[inline]
+    #[must_use]
+    pub const fn omitted(&self) -> usize {
+        self.omitted
+    }
+
+    /// The number of omitted errors
+    #[cfg(feature = "serde")]
+    pub(crate) const fn omitted_errors(&self) -> usize {
+        self.omitted_errors
+    }
+
+    /// Count additional omitted invalidities, including errors
+    #[cfg(feature = "serde")]
+    pub(crate) fn omit(mut self, omitted: usize, omitted_errors: usize) -> Self {
+        self.omitted = self.omitted.merge(omitted);
+        self.omitted_errors = self.omitted_errors.merge(omitted_errors);
+        self
+    }
+
+    /// Check if the context operates in fail-fast mode
+    #[inline]
+    #[must_use]
+    pub const fn is_fail_fast(&self) -> bool {
+        self.fail_fast
+    }
+
+    /// A new, empty context with the same mode, limit and omitted
+    /// invalidities
+    fn emptied(&self) -> Self {
+        let Self {
+            omitted,
+            omitted_errors,
+            ..
+        } = *self;
+        Self {
+            omitted,
+            omitted_errors,
+            ..self.empty_like()
+        }
+    }
+
+    /// A new, empty context with the same mode and limit
+    fn empty_like(&self) -> Self {
+        let Self {
+            fail_fast, limit, ..
+        } = *self;
+        Self {
+            fail_fast,
+            limit,
+            ..Self::default()
+        }
+    }
+
+    /// The remaining number of invalidities that could be stored,
+    /// if bounded
+    fn remaining(&self) -> Option<usize> {
+        self.limit()
+            .map(|limit| limit.saturating_sub(self.iter().len()))
+    }
+
+    /// Check if no more invalidities could be stored
+    fn is_full(&self) -> bool {
+        self.remaining() == Some(0)
+    }
+
+    /// Count the entries as omitted without storing them
+    fn omit_entries<U>(&mut self, entries: impl Iterator<Item = Entry<U>>) {
+        let mut omitted_errors = 0;
+        self.omitted = self.omitted.merge_iter(
+            None,
+            entries.map(|entry| {
+                omitted_errors += usize::from(entry.is_error());
+                1
+            }),
+        );
+        self.omitted_errors = self.omitted_errors.merge(omitted_errors);
+    }
+
+    /// A new, empty context with the same storage for validating a
+    /// component in the same mode
+    ///
+    /// The limit is reduced to the remaining capacity of this context.
+    fn scope(&self) -> Self {
+        Self {
+            fail_fast: self.fail_fast,
+            limit: self.remaining(),
+            ..Self::default()
+        }
+    }
+
+    /// A new, empty context for validating a component in the same mode
+    ///
+    /// The limit is reduced to the remaining capacity of this context.
+    pub(crate) fn nested<U, T>(&self) -> Context<U, T>
+    where
+        U: Invalidity,
+        T: Storage<U>,
+    {
+        Context {
+            fail_fast: self.fail_fast,
+            limit: self.remaining(),
+            ..Context::default()
+        }
+    }
+
+    /// Merge the mapped invalidities of another context
+    ///
+    /// Only the invalidities that are actually stored are mapped,
+    /// all others are just counted as omitted.
+    pub(crate) fn merge_mapped<U, T>(
+        self,
+        other: Context<U, T>,
+        map: impl FnMut(Entry<U>) -> Entry<V>,
+    ) -> Self
+    where
+        U: Invalidity,
+        T: Storage<U>,
+    {
+        if self.is_done() {
+            return self;
+        }
+        let Context {
+            invalidities,
+            omitted,
+            omitted_errors,
+            ..
+        } = other;
+        let count = invalidities.entries().len();
+        let capacity = self.remaining().unwrap_or(count);
+        let mut entries = invalidities.into_iter();
+        let mut merged = self.merge_iter(
+            count.min(capacity),
+            entries.by_ref().take(capacity).map(map),
+        );
+        merged.omit_entries(entries);
+        if !merged.is_done() {
+            merged.omitted = merged.omitted.merge(omitted);
+            merged.omitted_errors = merged.omitted_errors.merge(omitted_errors);
+        }
+        merged
+    }
+
+    /// Check if all further validations could be skipped
+    ///
+    /// Only fail-fast contexts are done after the first error has
+    /// been recorded. Needed for skipping the remaining components
+    /// when implementing [`Validate::validate_within()`].
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.fail_fast && !self.is_valid()
     }
 
     /// Check if the context is still valid
+    ///
+    /// The context is valid if no errors have been recorded,
+    /// independent of any warnings or infos.
     #[inline]
     #[must_use]
     pub fn is_valid(&self) -> bool {
-        self.is_empty()
+        self.omitted_errors == 0 && !self.iter().any(Entry::is_error)
+    }
+
+    /// Count all errors, including omitted errors
+    ///
+    /// Warnings and infos are not counted.
+    #[must_use]
+    pub fn count_errors(&self) -> usize {
+        self.omitted_errors + self.iter().filter(|entry| entry.is_error()).count()
+    }
+
+    /// Iterate over all collected invalidities
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &Entry<V>> {
+        self.invalidities.entries()
+    }
+
+    /// The number of collected invalidities
+    ///
+    /// Omitted invalidities are not included. The context might
+    /// not be [empty](IsEmpty::is_empty) even if no invalidities
+    /// have been collected.
+    #[expect(
+        clippy::len_without_is_empty,
+        reason = "implemented by IsEmpty that also accounts for omitted invalidities"
+    )]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.iter().len()
+    }
+
+    /// The first collected invalidity, if any
+    #[must_use]
+    pub fn first(&self) -> Option<&Entry<V>> {
+        self.iter().next()
+    }
+
+    /// Check if the given invalidity has been collected
+    ///
+    /// The path and severity are ignored.
+    #[must_use]
+    pub fn contains(&self, invalidity: &V) -> bool
+    where
+        V: PartialEq,
+    {
+        self.iter().any(|entry| entry.invalidity == *invalidity)
+    }
+
+    /// Keep only the invalidities that satisfy the predicate
+    ///
+    /// Omitted invalidities are still accounted for.
+    #[must_use]
+    pub fn retain(self, predicate: impl FnMut(&Entry<V>) -> bool) -> Self {
+        let empty = self.emptied();
+        let Self { invalidities, .. } = self;
+        let count = invalidities.entries().len();
+        empty.merge_iter(count, invalidities.into_iter().filter(predicate))
+    }
+
+    /// Create a new context with clones of the invalidities that
+    /// satisfy the predicate
+    ///
+    /// The new context operates in the same mode as this context.
+    /// Omitted invalidities are not accounted for.
+    #[must_use]
+    pub fn filter(&self, mut predicate: impl FnMut(&Entry<V>) -> bool) -> Self
+    where
+        V: Clone,
+    {
+        self.empty_like()
+            .merge_iter(None, self.iter().filter(|entry| predicate(entry)).cloned())
+    }
+
+    /// Split the invalidities into two contexts, depending on whether
+    /// they satisfy the predicate or not
+    ///
+    /// Both contexts operate in the same mode as this context.
+    /// Omitted invalidities are not accounted for.
+    #[must_use]
+    pub fn partition(self, mut predicate: impl FnMut(&Entry<V>) -> bool) -> (Self, Self) {
+        let init = (self.empty_like(), self.empty_like());
+        self.into_iter().fold(init, |(matching, other), entry| {
+            if predicate(&entry) {
+                (matching.merge_iter(1, once(entry)), other)
+            } else {
+                (matching, other.merge_iter(1, once(entry)))
+            }
+        })
+    }
+
+    /// Map all invalidities into another type
+    ///
+    /// The mapped context operates in the same mode as this context
+    /// and accounts for all omitted invalidities. The mapped invalidities
+    /// are stored in the [nested storage](Storage::Nested).
+    #[must_use]
+    pub fn map<U>(self, mut map: impl FnMut(V) -> U) -> Context<U, S::Nested<U>>
+    where
+        U: Invalidity,
+    {
+        let Self {
+            fail_fast, limit, ..
+        } = self;
+        Context {
+            fail_fast,
+            limit,
+            ..Context::default()
+        }
+        .merge_mapped(self, |entry| entry.map(&mut map))
+    }
+
+    /// Record an invalidity
+    ///
+    /// Neither the path nor the invalidity are created if the
+    /// invalidity is omitted.
+    fn record(
+        mut self,
+        path: impl FnOnce() -> Path,
+        severity: Severity,
+        invalidity: impl Into<V>,
+    ) -> Self {
+        if self.is_done() {
+            return self;
+        }
+        if self.is_full() {
+            self.omitted = self.omitted.merge(1);
+            self.omitted_errors = self.omitted_errors.merge(usize::from(severity.is_error()));
+            return self;
+        }
+        self.merge_iter(
+            1,
+            once(Entry {
+                path: path(),
+                severity,
+                invalidity: invalidity.into(),
+            }),
+        )
     }
 
     /// Record a new invalidity within this context
     #[inline]
     #[must_use]
     pub fn invalidate(self, invalidity: impl Into<V>) -> Self {
-        self.merge_iter(1, once(invalidity.into()))
+        self.record(Path::root, Severity::Error, invalidity)
+    }
+
+    /// Record a new invalidity of a component within this context
+    #[inline]
+    #[must_use]
+    pub fn invalidate_at(self, segment: impl Into<PathSegment>, invalidity: impl Into<V>) -> Self {
+        self.record(|| Path::from_iter([segment]), Severity::Error, invalidity)
+    }
+
+    /// Record a new warning within this context
+    ///
+    /// Warnings don't render the context invalid. They are discarded
+    /// when finishing a valid context [into](Self::into_result) a result
+    /// and only reported by validations
+    /// [within](crate::Validate::validate_within) a context.
+    #[inline]
+    #[must_use]
+    pub fn warn(self, invalidity: impl Into<V>) -> Self {
+        self.record(Path::root, Severity::Warning, invalidity)
+    }
+
+    /// Conditionally record a new warning within this context
+    #[inline]
+    #[must_use]
+    pub fn warn_if(self, is_unusual: impl Into<bool>, invalidity: impl Into<V>) -> Self {
+        if is_unusual.into() {
+            self.warn(invalidity)
+        } else {
+            self
+        }
+    }
+
+    /// Record a new info within this context
+    ///
+    /// Infos don't render the context invalid. Like
+    /// [warnings](Self::warn) they are only reported by validations
+    /// within a context.
+    #[inline]
+    #[must_use]
+    pub fn inform(self, invalidity: impl Into<V>) -> Self {
+        self.record(Path::root, Severity::Info, invalidity)
+    }
+
+    /// Conditionally record a new info within this context
+    #[inline]
+    #[must_use]
+    pub fn inform_if(self, is_notable: impl Into<bool>, invalidity: impl Into<V>) -> Self {
+        if is_notable.into() {
+            self.inform(invalidity)
+        } else {
+            self
+        }
     }
 
     /// Conditionally record a new invalidity within this context
@@ -102,14 +793,48 @@ where
         }
     }
 
+    /// Merge another context, possibly with a different storage
+    #[inline]
+    #[must_use]
+    pub fn merge<T>(self, other: Context<V, T>) -> Self
+    where
+        T: Storage<V>,
+    {
+        self.merge_mapped(other, identity)
+    }
+
+    /// Convert into a context with a different storage
+    ///
+    /// Needed for finishing a validation with a custom storage or
+    /// capacity, e.g. `InlineContext::<_, 2>::default()` in a
+    /// `Validate` implementation.
+    #[must_use]
+    pub fn into_storage<T>(self) -> Context<V, T>
+    where
+        T: Storage<V>,
+    {
+        let Self {
+            fail_fast, limit, ..
+        } = self;
+        Context {
+            fail_fast,
+            limit,
+            ..Default::default()
+        }
+        .merge(self)
+    }
+
     /// Merge the results of another validation
     ///
     /// Needed for collecting results from custom validation functions.
     #[inline]
     #[must_use]
-    pub fn merge_result(self, res: ValidationResult<V>) -> Self {
+    pub fn merge_result<T>(self, res: UnitResult<Context<V, T>>) -> Self
+    where
+        T: Storage<V>,
+    {
         if let Err(other) = res {
-            self.merge(other)
+            self.merge_mapped(other, identity)
         } else {
             self
         }
@@ -119,13 +844,37 @@ where
     ///
     /// Needed for collecting results from custom validation functions.
     #[must_use]
-    pub fn merge_result_with<F, U>(self, res: ValidationResult<U>, map: F) -> Self
+    pub fn merge_result_with<F, U, T>(self, res: UnitResult<Context<U, T>>, map: F) -> Self
     where
         F: Fn(U) -> V,
         U: Invalidity,
+        T: Storage<U>,
     {
         if let Err(other) = res {
-            self.merge_exact_size_iter(other.invalidities.into_iter().map(map))
+            self.merge_mapped(other, |entry| entry.map(&map))
+        } else {
+            self
+        }
+    }
+
+    /// Merge the mapped results of another validation of a component
+    ///
+    /// The paths of all merged invalidities are prefixed with `segment`.
+    #[must_use]
+    pub fn merge_result_at_with<F, U, T>(
+        self,
+        segment: impl Into<PathSegment>,
+        res: UnitResult<Context<U, T>>,
+        map: F,
+    ) -> Self
+    where
+        F: Fn(U) -> V,
+        U: Invalidity,
+        T: Storage<U>,
+    {
+        if let Err(other) = res {
+            let mut prepend = prepend_lazily(segment);
+            self.merge_mapped(other, |entry| prepend(entry).map(&map))
         } else {
             self
         }
@@ -149,42 +898,203 @@ where
         F: Fn(U) -> V,
         U: Invalidity,
     {
-        self.merge_result_with(target.validate(), map)
+        if self.is_done() {
+            return self;
+        }
+        let validated = target.validate_within(self.nested::<U, S::Nested<U>>());
+        self.merge_mapped(validated, |entry| entry.map(&map))
+    }
+
+    /// Validate a component and merge the result into this context
+    ///
+    /// The invalidities of the component are recorded at `segment`.
+    #[inline]
+    #[must_use]
+    pub fn validate_at<U>(
+        self,
+        segment: impl Into<PathSegment>,
+        target: &impl Validate<Invalidity = U>,
+    ) -> Self
+    where
+        U: Invalidity + Into<V>,
+    {
+        self.validate_at_with(segment, target, Into::into)
+    }
+
+    /// Validate a component and merge the mapped result into this context
+    ///
+    /// The invalidities of the component are recorded at `segment`.
+    #[inline]
+    #[must_use]
+    pub fn validate_at_with<F, U>(
+        self,
+        segment: impl Into<PathSegment>,
+        target: &impl Validate<Invalidity = U>,
+        map: F,
+    ) -> Self
+    where
+        F: Fn(U) -> V,
+        U: Invalidity,
+    {
+        if self.is_done() {
+            return self;
+        }
+        let validated = target.validate_within(self.nested::<U, S::Nested<U>>());
+        self.merge_mapped_at(segment, validated, map)
+    }
+
+    /// Merge the mapped invalidities of another context at `segment`
+    pub(crate) fn merge_mapped_at<U, T>(
+        self,
+        segment: impl Into<PathSegment>,
+        other: Context<U, T>,
+        map: impl Fn(U) -> V,
+    ) -> Self
+    where
+        U: Invalidity,
+        T: Storage<U>,
+    {
+        let mut prepend = prepend_lazily(segment);
+        self.merge_mapped(other, |entry| prepend(entry).map(&map))
+    }
+
+    /// Perform validations of a component within a nested scope
+    ///
+    /// All invalidities that are recorded within `scope` are merged
+    /// into this context at `segment`. The segment is only converted
+    /// if invalidities are stored. The nested context operates
+    /// in the same mode as this context.
+    #[must_use]
+    pub fn scoped(self, segment: impl Into<PathSegment>, scope: impl FnOnce(Self) -> Self) -> Self {
+        if self.is_done() {
+            return self;
+        }
+        let scoped = scope(self.scope());
+        self.merge_mapped(scoped, prepend_lazily(segment))
     }
 
     /// Finish the validation
     ///
     /// Finishes the current validation of this context with a result.
+    /// All warnings and infos are discarded if the context is valid.
     ///
     /// # Errors
     ///
     /// Returns `Err` with the collected invalidities if one or more
     /// validations failed.
     #[inline]
-    pub fn into_result(self) -> ValidationResult<V> {
+    pub fn into_result(self) -> UnitResult<Self> {
         if self.is_valid() { Ok(()) } else { Err(self) }
     }
 }
 
-impl<V> From<Context<V>> for ValidationResult<V>
+/// Finishes the validation of `Validate` implementations
+///
+/// Equivalent to [`Context::into_result()`], i.e. the result is `Err`
+/// only if the context is invalid. Warnings and infos of a valid
+/// context are discarded. They are only reported when validating
+/// [within](crate::Validate::validate_within) a context, e.g. by
+/// [`ValidatedFrom`](crate::ValidatedFrom).
+impl<V, S> From<Context<V, S>> for UnitResult<Context<V, S>>
 where
     V: Invalidity,
+    S: Storage<V>,
 {
-    fn from(from: Context<V>) -> Self {
+    fn from(from: Context<V, S>) -> Self {
         from.into_result()
     }
 }
 
+/// Lists all invalidities together with their paths in dotted notation
+///
+/// The output is intended for logging and error messages. Invalidities
+/// are formatted with `Debug` and separated by `; `. The severity is
+/// appended to warnings and infos.
+impl<V, S> fmt::Display for Context<V, S>
+where
+    V: Invalidity,
+    S: Storage<V>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (position, entry) in self.iter().enumerate() {
+            let Entry {
+                path,
+                severity,
+                invalidity,
+            } = entry;
+            if position > 0 {
+                f.write_str("; ")?;
+            }
+            if path.is_root() {
+                write!(f, "{invalidity:?}")?;
+            } else {
+                write!(f, "{path}: {invalidity:?}")?;
+            }
+            match severity {
+                Severity::Error => (),
+                Severity::Warning => f.write_str(" (warning)")?,
+                Severity::Info => f.write_str(" (info)")?,
+            }
+        }
+        match self.omitted {
+            0 => Ok(()),
+            omitted => {
+                if self.iter().len() > 0 {
+                    f.write_str("; ")?;
+                }
+                if omitted == 1 {
+                    f.write_str("1 more invalidity omitted")
+                } else {
+                    write!(f, "{omitted} more invalidities omitted")
+                }
+            }
+        }
+    }
+}
+
+/// Collect errors at the root path
+impl<V, S> FromIterator<V> for Context<V, S>
+where
+    V: Invalidity,
+    S: Storage<V>,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+    {
+        let mut context = Self::default();
+        context.extend(iter);
+        context
+    }
+}
+
+/// Record errors at the root path
+impl<V, S> Extend<V> for Context<V, S>
+where
+    V: Invalidity,
+    S: Storage<V>,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = V>,
+    {
+        *self = iter
+            .into_iter()
+            .fold(core::mem::take(self), Self::invalidate);
+    }
+}
+
 /// Transform the validation context into an iterator
 /// that yields all the collected invalidities.
-impl<V> IntoIterator for Context<V>
+impl<V, S> IntoIterator for Context<V, S>
 where
     V: Invalidity,
+    S: Storage<V>,
 {
-    type Item = V;
+    type Item = Entry<V>;
     // TODO: Replace with an opaque, existential type eventually (if ever possible):
     // type IntoIter = impl Iterator<V>;
-    type IntoIter = smallvec::IntoIter<SmallVecArray<V>>;
+    type IntoIter = S::IntoIter;
 
     fn into_iter(self) -> Self::IntoIter {
         self.invalidities.into_iter()
@@ -195,6 +1105,10 @@ where
 mod tests {
     use super::*;
 
+    use alloc::{string::ToString as _, vec, vec::Vec};
+
+    use crate::ValidationResult;
+
     #[test]
     fn valid_context() {
         let context = Context::<()>::new();
@@ -224,4 +1138,335 @@ mod tests {
         assert_eq!(SMALLVEC_ARRAY_LEN + 1, context.invalidities.len());
         assert!(context.into_result().is_err());
     }
+
+    #[test]
+    fn paths() {
+        let context = Context::<()>::new()
+            .invalidate(())
+            .invalidate_at("field", ())
+            .scoped("outer", |context| {
+                context
+                    .invalidate(())
+                    .scoped(1, |context| context.invalidate_at("inner", ()))
+            });
+        assert_eq!(
+            vec![
+                Path::root(),
+                Path::from_iter(["field"]),
+                Path::from_iter(["outer"]),
+                Path::from_iter([
+                    PathSegment::field("outer"),
+                    PathSegment::Index(1),
+                    PathSegment::field("inner"),
+                ]),
+            ],
+            context
+                .into_iter()
+                .map(|entry| entry.path)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("", Context::<()>::new().to_string());
+        assert_eq!(
+            "(); items[1].name: ()",
+            Context::<()>::new()
+                .invalidate(())
+                .scoped("items", |context| {
+                    context.scoped(1, |context| context.invalidate_at("name", ()))
+                })
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn fail_fast() {
+        let context = Context::<u8>::fail_fast()
+            .invalidate(1)
+            .invalidate(2)
+            .scoped("field", |_| unreachable!())
+            .merge_result(Err(Context::new().invalidate(3)));
+        assert!(context.is_fail_fast());
+        assert_eq!(
+            vec![Entry::error(Path::root(), 1)],
+            context.into_iter().collect::<Vec<_>>()
+        );
+
+        let context = Context::<u8>::fail_fast().scoped("field", |context| {
+            assert!(context.is_fail_fast());
+            context.invalidate(1).invalidate(2)
+        });
+        assert_eq!(
+            vec![Entry::error(Path::from_iter(["field"]), 1)],
+            context.into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn bounded() {
+        let context = Context::<u8>::bounded(2).invalidate(1);
+        assert_eq!(Some(2), context.limit());
+        assert_eq!((1, 0), (context.iter().len(), context.omitted()));
+        let context = context
+            .invalidate(2)
+            .invalidate(3)
+            .merge_result(Err(Context::<u8>::bounded(1).invalidate(4).invalidate(5)));
+        assert_eq!((2, 3), (context.iter().len(), context.omitted()));
+        assert_eq!("1; 2; 3 more invalidities omitted", context.to_string());
+
+        // Nested contexts are bounded by the same limit
+        let context = Context::<u8>::new().scoped("field", |context| {
+            assert_eq!(None, context.limit());
+            context
+        });
+        assert!(context.is_valid());
+        let context = Context::<u8>::bounded(1).scoped("field", |context| {
+            assert_eq!(Some(1), context.limit());
+            context.invalidate(1).invalidate(2)
+        });
+        assert_eq!((1, 1), (context.iter().len(), context.omitted()));
+        assert_eq!("field: 1; 1 more invalidity omitted", context.to_string());
+
+        // Invalid even if all invalidities have been omitted
+        let context = Context::<u8>::bounded(0).invalidate(1);
+        assert!(!context.is_valid());
+        assert_eq!("1 more invalidity omitted", context.to_string());
+    }
+
+    #[test]
+    fn counting() {
+        let context = Context::<u8>::counting()
+            .invalidate(1)
+            .warn(2)
+            .scoped("field", |context| {
+                assert!(context.is_counting());
+                context.invalidate(3)
+            })
+            .merge_result(Err(Context::new().invalidate(4).inform(5)));
+        assert!(!context.is_valid());
+        assert_eq!(0, context.iter().len());
+        assert_eq!(5, context.omitted());
+        assert_eq!(3, context.count_errors());
+
+        let context = Context::<u8>::bounded(1)
+            .warn(1)
+            .invalidate(2)
+            .invalidate(3);
+        assert_eq!(2, context.count_errors());
+    }
+
+    /// Omitted invalidities are neither converted nor located
+    #[test]
+    fn omit_without_conversion() {
+        struct Unconverted;
+        impl From<Unconverted> for u8 {
+            fn from(_: Unconverted) -> Self {
+                unreachable!()
+            }
+        }
+        impl From<Unconverted> for PathSegment {
+            fn from(_: Unconverted) -> Self {
+                unreachable!()
+            }
+        }
+        let context = Context::<u8>::counting()
+            .invalidate(Unconverted)
+            .invalidate_at(Unconverted, Unconverted)
+            .warn(Unconverted)
+            .scoped("field", |context| context.invalidate(Unconverted));
+        assert_eq!((4, 3), (context.omitted(), context.count_errors()));
+    }
+
+    #[test]
+    fn checking() {
+        let context = Context::<u8>::checking()
+            .warn(1)
+            .invalidate(2)
+            .invalidate(3)
+            .scoped("field", |_| unreachable!());
+        assert!(context.is_fail_fast() && context.is_counting());
+        assert!(!context.is_valid());
+        assert_eq!((0, 2), (context.len(), context.omitted()));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn custom_storage() {
+        let context = Context::<u8, Vec<Entry<u8>>>::bounded(2)
+            .invalidate(1)
+            .scoped("field", |context| context.warn(2))
+            .merge_result(Err(Context::new().invalidate(3)));
+        assert!(!context.is_valid());
+        assert_eq!(1, context.omitted());
+        assert_eq!(
+            "1; field: 2 (warning); 1 more invalidity omitted",
+            context.to_string()
+        );
+        assert!(context.into_result().is_err());
+
+        let context = InlineContext::<u8, 2>::bounded(1)
+            .invalidate(1)
+            .invalidate(2)
+            .into_storage::<Vec<_>>();
+        assert_eq!(Some(1), context.limit());
+        assert_eq!((1, 1), (context.iter().len(), context.omitted()));
+    }
+
+    #[test]
+    fn inline_capacity() {
+        let context = InlineContext::<u8, 1>::default()
+            .invalidate(1)
+            .merge(InlineContext::<u8, 32>::default().invalidate(2))
+            .merge_result(Err(Context::new().invalidate(3)))
+            .merge_result_with(Err(InlineContext::<(), 2>::default().warn(())), |()| 4)
+            .scoped("field", |context| context.invalidate(5));
+        assert_eq!("1; 2; 3; 4 (warning); field: 5", context.to_string());
+        let context: Context<u8> = context.into_storage();
+        assert_eq!(5, context.iter().len());
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn fixed_capacity() {
+        let context = FixedContext::<u8, 2>::default()
+            .warn(1)
+            .invalidate_if(false, 2);
+        assert_eq!(Some(2), context.limit());
+        assert!(context.is_valid());
+        let context = context
+            .invalidate(4)
+            .merge_result(Err(Context::new().invalidate(5).warn(6)));
+        assert!(!context.is_valid());
+        assert_eq!(
+            "1 (warning); 4; 2 more invalidities omitted",
+            context.to_string()
+        );
+        assert!(context.clone().into_result().is_err());
+        let context: Context<u8> = context.into_storage();
+        assert_eq!(2, context.iter().len());
+
+        // Bounded by the smaller limit
+        assert_eq!(Some(1), FixedContext::<u8, 2>::bounded(1).limit());
+        assert_eq!(Some(2), FixedContext::<u8, 2>::bounded(3).limit());
+    }
+
+    #[test]
+    fn severities() {
+        let context = Context::<u8>::new()
+            .warn(1)
+            .warn_if(false, 2)
+            .inform(3)
+            .inform_if(false, 4)
+            .scoped("field", |context| context.warn(5));
+        assert!(context.is_valid());
+        assert!(!context.is_empty());
+        assert_eq!(
+            "1 (warning); 3 (info); field: 5 (warning)",
+            context.to_string()
+        );
+        assert!(context.clone().into_result().is_ok());
+        assert_eq!(Ok(()), ValidationResult::from(context.clone()));
+        assert!(!context.invalidate(6).is_valid());
+    }
+
+    #[test]
+    fn fail_fast_after_warnings() {
+        let context = Context::<u8>::fail_fast()
+            .warn(1)
+            .scoped("field", |context| context.invalidate(2))
+            .invalidate(3);
+        assert_eq!(
+            vec![Severity::Warning, Severity::Error],
+            context
+                .into_iter()
+                .map(|entry| entry.severity)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn bounded_omitted_severities() {
+        let context = Context::<u8>::bounded(1).invalidate(1).warn(2);
+        assert!(!context.is_valid());
+        let context = Context::<u8>::bounded(1).warn(1).warn(2);
+        assert!(context.is_valid());
+        let context = Context::<u8>::bounded(1).warn(1).invalidate(2);
+        assert!(!context.is_valid());
+        assert!(!Context::<u8>::new().merge(context).is_valid());
+    }
+
+    #[test]
+    fn scoped_valid() {
+        let context = Context::<()>::new().scoped("field", |context| context);
+        assert!(context.is_valid());
+    }
+
+    #[test]
+    fn query() {
+        let context = Context::<u8>::new()
+            .invalidate(1)
+            .scoped("field", |context| context.warn(2).invalidate(3));
+        assert_eq!(3, context.len());
+        assert_eq!(Some(&Entry::error(Path::root(), 1)), context.first());
+        assert!(context.contains(&2));
+        assert!(!context.contains(&4));
+        assert!(Context::<u8>::new().first().is_none());
+    }
+
+    #[test]
+    fn retain() {
+        let context = Context::<u8>::bounded(2)
+            .invalidate(1)
+            .warn(2)
+            .invalidate(3)
+            .retain(|entry| !entry.is_error());
+        assert_eq!(1, context.len());
+        assert_eq!(1, context.omitted());
+        assert!(!context.is_valid());
+    }
+
+    #[test]
+    fn filter_and_partition() {
+        let context = Context::<u8>::new()
+            .invalidate(1)
+            .warn(2)
+            .invalidate_at("field", 3);
+        let errors = context.filter(Entry::is_error);
+        assert_eq!(Context::from_iter([1]).invalidate_at("field", 3), errors);
+        let (errors, others) = context.partition(Entry::is_error);
+        assert_eq!(2, errors.count_errors());
+        assert_eq!(Context::new().warn(2), others);
+        assert!(others.is_valid());
+    }
+
+    #[test]
+    fn map() {
+        let context = Context::<u8>::bounded(1)
+            .invalidate(1)
+            .invalidate_at("field", 2)
+            .map(u16::from);
+        assert_eq!(Some(&Entry::error(Path::root(), 1u16)), context.first());
+        assert_eq!(Some(1), context.limit());
+        assert_eq!(1, context.omitted());
+        assert_eq!(2, context.count_errors());
+
+        // The mapped invalidities are stored in the same kind of storage
+        let context: InlineContext<u16, 1> = InlineContext::<u8, 1>::default()
+            .invalidate(1)
+            .map(u16::from);
+        assert_eq!(1, context.len());
+    }
+
+    #[test]
+    fn collect_and_extend() {
+        let mut context: Context<u8> = [1, 2].into_iter().collect();
+        context.extend([3]);
+        assert_eq!(3, context.count_errors());
+        assert!(context.iter().all(|entry| entry.path.is_root()));
+        let mut context = Context::<u8>::fail_fast();
+        context.extend([1, 2]);
+        assert_eq!(1, context.len());
+    }
 }
@@ -0,0 +1,296 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Type-erased validation of heterogeneous components
+
+use alloc::boxed::Box;
+use core::any::Any;
+
+use crate::{
+    Invalidity, Validate, ValidationResult,
+    context::{Context, Storage},
+};
+
+/// A context that collects invalidities of arbitrary types
+///
+/// Merges the results of components with invalidity types that are
+/// unknown at compile time, e.g. plugins. The invalidities could be
+/// downcasted into their concrete types.
+pub type AnyContext = Context<Box<dyn Invalidity>>;
+
+/// Box an invalidity unless it is already a type-erased invalidity
+fn into_box<V>(invalidity: V) -> Box<dyn Invalidity>
+where
+    V: Invalidity,
+{
+    let mut invalidity = Some(invalidity);
+    let boxed = (&mut invalidity as &mut dyn Any)
+        .downcast_mut::<Option<Box<dyn Invalidity>>>()
+        .and_then(Option::take);
+    match (boxed, invalidity) {
+        (Some(boxed), _) => boxed,
+        (None, Some(invalidity)) => Box::new(invalidity),
+        (None, None) => unreachable!("only taken if already boxed"),
+    }
+}
+
+impl<V, S> Context<V, S>
+where
+    V: Invalidity,
+    S: Storage<V>,
+{
+    /// Erase the type of all invalidities
+    ///
+    /// The type-erased context operates in the same mode as this context
+    /// and stores the invalidities in the [nested storage](Storage::Nested).
+    /// Invalidities that are already type-erased are not boxed again,
+    /// i.e. the invalidities of an [`AnyContext`] are returned unchanged.
+    #[must_use]
+    pub fn into_any(self) -> Context<Box<dyn Invalidity>, S::Nested<Box<dyn Invalidity>>> {
+        self.map(into_box)
+    }
+}
+
+impl AnyContext {
+    /// Iterate over all collected invalidities of type `T`
+    pub fn downcast_iter<T>(&self) -> impl Iterator<Item = &T>
+    where
+        T: Any,
+    {
+        self.iter()
+            .filter_map(|entry| entry.invalidity.downcast_ref::<T>())
+    }
+
+    /// Restore the type of all invalidities
+    ///
+    /// # Errors
+    ///
+    /// Returns the unmodified context as `Err` if not all invalidities
+    /// are of type `V`.
+    #[expect(
+        clippy::missing_panics_doc,
+        reason = "the types of all invalidities are checked in advance"
+    )]
+    #[expect(
+        clippy::result_large_err,
+        reason = "returns the context unmodified like a validation result"
+    )]
+    pub fn downcast<V>(self) -> Result<Context<V>, Self>
+    where
+        V: Invalidity,
+    {
+        if !self.iter().all(|entry| entry.invalidity.is::<V>()) {
+            return Err(self);
+        }
+        Ok(self.map(|invalidity| {
+            *(invalidity as Box<dyn Any>)
+                .downcast::<V>()
+                .expect("invalidity of type V")
+        }))
+    }
+}
+
+/// An object-safe variant of [`Validate`]
+///
+/// Implemented for all types that implement [`Validate`]. The
+/// invalidities are type-erased, which allows to validate
+/// heterogeneous components as trait objects, e.g. in a
+/// `Vec<Box<dyn DynValidate>>`.
+pub trait DynValidate {
+    /// Perform the validation
+    ///
+    /// See also: [`Validate::validate()`]
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` with the collected invalidities if one or more
+    /// validations failed.
+    #[expect(
+        clippy::result_large_err,
+        reason = "the canonical result of validations"
+    )]
+    fn validate_dyn(&self) -> ValidationResult<Box<dyn Invalidity>>;
+
+    /// Perform the validation within the given context
+    ///
+    /// See also: [`Validate::validate_within()`]
+    #[must_use]
+    fn validate_dyn_within(&self, context: AnyContext) -> AnyContext;
+}
+
+impl<T> DynValidate for T
+where
+    T: Validate,
+{
+    fn validate_dyn(&self) -> ValidationResult<Box<dyn Invalidity>> {
+        self.validate().map_err(Context::into_any)
+    }
+
+    fn validate_dyn_within(&self, context: AnyContext) -> AnyContext {
+        context.validate_with(self, into_box)
+    }
+}
+
+macro_rules! impl_validate_for_dyn_validate {
+    ($($bounds:tt)*) => {
+        /// Validate trait objects with type-erased invalidities
+        impl Validate for dyn DynValidate $($bounds)* {
+            type Invalidity = Box<dyn Invalidity>;
+
+            fn validate(&self) -> ValidationResult<Self::Invalidity> {
+                self.validate_dyn()
+            }
+
+            fn validate_within<S>(
+                &self,
+                context: Context<Self::Invalidity, S>,
+            ) -> Context<Self::Invalidity, S>
+            where
+                S: Storage<Self::Invalidity>,
+            {
+                if context.is_done() {
+                    return context;
+                }
+                let validated = self.validate_dyn_within(context.nested());
+                context.merge(validated)
+            }
+        }
+    };
+}
+
+impl_validate_for_dyn_validate!();
+impl_validate_for_dyn_validate!(+ Send);
+impl_validate_for_dyn_validate!(+ Send + Sync);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{vec, vec::Vec};
+
+    use crate::{
+        IsValid as _,
+        context::{InlineContext, InlineStorage},
+        path::PathSegment,
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum QuantityInvalidity {
+        Zero,
+    }
+
+    struct Quantity(u32);
+
+    impl Validate for Quantity {
+        type Invalidity = QuantityInvalidity;
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            Context::new()
+                .invalidate_if(self.0 == 0, QuantityInvalidity::Zero)
+                .into()
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum NameInvalidity {
+        Empty,
+    }
+
+    struct Name(&'static str);
+
+    impl Validate for Name {
+        type Invalidity = NameInvalidity;
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            Context::new()
+                .invalidate_if(self.0.is_empty(), NameInvalidity::Empty)
+                .into()
+        }
+    }
+
+    #[test]
+    fn any_context() {
+        let context = AnyContext::new()
+            .merge(
+                Context::<QuantityInvalidity>::new()
+                    .invalidate(QuantityInvalidity::Zero)
+                    .into_any(),
+            )
+            .validate_at("name", &(&Name("") as &dyn DynValidate));
+        assert_eq!(2, context.count_errors());
+        assert_eq!(
+            vec![&QuantityInvalidity::Zero],
+            context
+                .downcast_iter::<QuantityInvalidity>()
+                .collect::<Vec<_>>()
+        );
+        assert!(
+            context
+                .first()
+                .unwrap()
+                .invalidity
+                .is::<QuantityInvalidity>()
+        );
+        let context = context.downcast::<NameInvalidity>().unwrap_err();
+        assert_eq!(
+            Ok(Context::new().invalidate(NameInvalidity::Empty)),
+            Context::<NameInvalidity>::new()
+                .invalidate(NameInvalidity::Empty)
+                .into_any()
+                .downcast::<NameInvalidity>()
+                .map_err(|_| ())
+        );
+        assert_eq!(2, context.len());
+
+        let context: Context<_, InlineStorage<_, 1>> =
+            InlineContext::<QuantityInvalidity, 1>::default()
+                .invalidate(QuantityInvalidity::Zero)
+                .into_any();
+        assert!(
+            context
+                .first()
+                .unwrap()
+                .invalidity
+                .is::<QuantityInvalidity>()
+        );
+    }
+
+    #[test]
+    fn dyn_validate() {
+        let components: [&dyn DynValidate; 4] = [&Quantity(1), &Name(""), &Quantity(0), &Name("a")];
+        let context = components.validate().unwrap_err();
+        assert_eq!(
+            vec![PathSegment::Index(1), PathSegment::Index(2)],
+            context
+                .iter()
+                .filter_map(|entry| entry.path.segments().first().cloned())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(1, context.downcast_iter::<NameInvalidity>().count());
+        assert_eq!(2, components.count_invalidities());
+        assert!(!components.is_valid());
+        assert_eq!(1, components.validate_fail_fast().unwrap_err().len());
+    }
+
+    #[test]
+    fn nested_dyn_validate() {
+        let nested: Box<dyn DynValidate> = Box::new(Box::new(Name("")) as Box<dyn DynValidate>);
+        let context = nested.validate_dyn().unwrap_err();
+        assert_eq!(1, context.downcast_iter::<NameInvalidity>().count());
+        let context = nested.validate_dyn_within(AnyContext::new());
+        assert_eq!(1, context.downcast_iter::<NameInvalidity>().count());
+        let components: Vec<Box<dyn DynValidate>> = vec![nested, Box::new(Quantity(0))];
+        let context = components.validate_dyn().unwrap_err();
+        assert_eq!(1, context.downcast_iter::<NameInvalidity>().count());
+        assert_eq!(1, context.downcast_iter::<QuantityInvalidity>().count());
+
+        // Type-erased invalidities are not boxed again
+        let context = context.into_any();
+        assert_eq!(2, context.len());
+        assert!(
+            context
+                .iter()
+                .all(|entry| !entry.invalidity.is::<Box<dyn Invalidity>>())
+        );
+    }
+}
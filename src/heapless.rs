@@ -0,0 +1,55 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Trait implementations for heapless
+
+use heapless::Vec;
+
+use crate::util::{IsEmpty, Mergeable};
+
+impl<T, const N: usize> IsEmpty for Vec<T, N> {
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+/// Items that exceed the fixed capacity are discarded
+impl<T, const N: usize> Mergeable for Vec<T, N> {
+    type Item = T;
+
+    fn empty<H>(_: H) -> Self {
+        Self::new()
+    }
+
+    fn merge(self, other: Self) -> Self {
+        self.merge_iter(None, other.into_iter())
+    }
+
+    fn merge_iter<H, I>(mut self, _: H, iter: I) -> Self
+    where
+        I: Iterator<Item = Self::Item>,
+    {
+        for item in iter {
+            if self.push(item).is_err() {
+                break;
+            }
+        }
+        self
+    }
+
+    fn capacity_limit(&self) -> Option<usize> {
+        Some(N)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mergeable() {
+        let vec = Vec::<u8, 3>::empty(None).merge_iter(None, [1, 2].into_iter());
+        assert_eq!(Some(3), vec.capacity_limit());
+        assert_eq!([1, 2, 3], vec.merge(Vec::from_slice(&[3, 4]).unwrap()));
+    }
+}
@@ -0,0 +1,849 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Lenient decoding of JSON values with a combined report
+//!
+//! Plain deserialization aborts on the first structural error before
+//! any semantic validation could be performed. Decoding objects field
+//! by field with [`JsonObject`] and arrays element by element collects
+//! all structural problems like missing fields or mismatching types as
+//! invalidities at their path. The semantic validations are then
+//! performed on everything that could be decoded, resulting in a single,
+//! combined report.
+//!
+//! Decoded fields are validated before they are moved into the object.
+//! Their invalidities are only reported if the object could not be
+//! decoded as a whole, otherwise the decoded object is validated.
+//! Neither fields nor elements are decoded twice.
+//!
+//! # Example
+//!
+//! ```
+//! # use semval::{json::{FromJsonValue, JsonDecoded, JsonInvalidity, JsonObject}, prelude::*};
+//! # use serde_json::json;
+//! #[derive(Debug)]
+//! struct Quantity(u32);
+//!
+//! impl Validate for Quantity {
+//!     type Invalidity = ();
+//!
+//!     fn validate(&self) -> ValidationResult<Self::Invalidity> {
+//!         ValidationContext::new().invalidate_if(self.0 == 0, ()).into()
+//!     }
+//! }
+//!
+//! #[derive(Debug)]
+//! struct Order {
+//!     item: String,
+//!     quantity: Quantity,
+//! }
+//!
+//! impl Validate for Order {
+//!     type Invalidity = ();
+//!
+//!     fn validate(&self) -> ValidationResult<Self::Invalidity> {
+//!         ValidationContext::new().validate_at("quantity", &self.quantity).into()
+//!     }
+//! }
+//!
+//! impl FromJsonValue for Order {
+//!     fn decode_json_value(value: &serde_json::Value) -> JsonDecoded<Self> {
+//!         let mut object = JsonObject::new(value)?;
+//!         let item = object.field("item");
+//!         let quantity = object.validated_field("quantity", Quantity, |()| ());
+//!         let order = (|| Some(Order { item: item?, quantity: quantity? }))();
+//!         object.finish(order)
+//!     }
+//! }
+//!
+//! let (order, context) = Order::from_json_value(&json!({ "quantity": 0 })).unwrap_err();
+//! assert!(order.is_none());
+//! assert_eq!(r#"item: Missing; quantity: Invalid(())"#, context.to_string());
+//! ```
+
+use core::fmt;
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::{
+    NestedInvalidity, Validate, Validated,
+    context::{Context, Entry},
+    path::{Path, PathSegment},
+    util::{IsEmpty as _, MergeableSized as _},
+};
+
+/// Structural or semantic invalidities of a JSON value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonInvalidity<V> {
+    /// A required value is missing
+    Missing,
+
+    /// The value could not be decoded
+    ///
+    /// Carries the message of the deserialization error.
+    Malformed(String),
+
+    /// The decoded value is semantically invalid
+    Invalid(V),
+}
+
+impl<V> JsonInvalidity<V> {
+    /// A value of an unexpected JSON type
+    ///
+    /// Only the type of the value is reported, not its contents.
+    fn unexpected(expected: &str, value: &Value) -> Self {
+        let found = match value {
+            Value::Null => "null",
+            Value::Bool(_) => "a boolean",
+            Value::Number(_) => "a number",
+            Value::String(_) => "a string",
+            Value::Array(_) => "an array",
+            Value::Object(_) => "an object",
+        };
+        Self::Malformed(format!("expected {expected}, found {found}"))
+    }
+}
+
+/// Exposes the semantic invalidity of the decoded value
+impl<V> NestedInvalidity for JsonInvalidity<V>
+where
+    V: NestedInvalidity,
+{
+    fn source(&self) -> Option<&dyn NestedInvalidity> {
+        match self {
+            Self::Missing | Self::Malformed(_) => None,
+            Self::Invalid(invalidity) => Some(invalidity),
+        }
+    }
+}
+
+/// Collected invalidities of a JSON value
+pub type JsonContext<T> = Context<JsonInvalidity<<T as Validate>::Invalidity>>;
+
+/// Result of decoding a JSON value without validating it
+///
+/// On failure the value is returned if it could be decoded despite
+/// of structural problems, together with all structural invalidities.
+/// If it could not be decoded then the semantic invalidities of all
+/// its components that could be decoded are included.
+pub type JsonDecoded<T> = Result<T, (Option<T>, JsonContext<T>)>;
+
+/// Result of decoding and validating a JSON value
+///
+/// On success the validated value is returned together with all
+/// warnings and infos. On failure the decoded value is returned if
+/// it could be decoded despite of structural problems, together with
+/// all invalidities.
+pub type JsonResult<T> = Result<(Validated<T>, JsonContext<T>), (Option<T>, JsonContext<T>)>;
+
+/// Decode and validate a value from JSON
+pub trait FromJsonValue: Validate + Sized {
+    /// Decode the value leniently without validating it
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` with the collected invalidities if decoding failed.
+    fn decode_json_value(value: &Value) -> JsonDecoded<Self>;
+
+    /// Decode the value leniently and validate it
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` with the collected structural and semantic
+    /// invalidities if decoding or validation failed.
+    fn from_json_value(value: &Value) -> JsonResult<Self> {
+        let (value, context) = match Self::decode_json_value(value) {
+            Ok(value) => (value, Context::new()),
+            Err((Some(value), context)) => (value, context),
+            Err((None, context)) => return Err((None, context)),
+        };
+        let context = context.validate_with(&value, JsonInvalidity::Invalid);
+        if context.is_valid() {
+            Ok((Validated(value), context))
+        } else {
+            Err((Some(value), context))
+        }
+    }
+}
+
+/// Decodes all elements leniently, recording their invalidities
+/// at their index
+impl<T> FromJsonValue for Vec<T>
+where
+    T: FromJsonValue,
+{
+    fn decode_json_value(value: &Value) -> JsonDecoded<Self> {
+        let Value::Array(array) = value else {
+            return Err((
+                None,
+                Context::new().invalidate(JsonInvalidity::unexpected("an array", value)),
+            ));
+        };
+        let mut elements = Vec::with_capacity(array.len());
+        let mut context = Context::new();
+        for (index, element) in array.iter().enumerate() {
+            let element = match T::decode_json_value(element) {
+                Ok(element) => Some(element),
+                Err((element, invalidities)) => {
+                    context = context.scoped(index, |context| context.merge(invalidities));
+                    element
+                }
+            };
+            elements.push(element);
+        }
+        if elements.iter().all(Option::is_some) {
+            let elements = elements.into_iter().flatten().collect();
+            return if context.is_empty() {
+                Ok(elements)
+            } else {
+                Err((Some(elements), context))
+            };
+        }
+        // Validate all elements that could be decoded separately
+        let context = elements
+            .iter()
+            .enumerate()
+            .filter_map(|(index, element)| Some((index, element.as_ref()?)))
+            .fold(context, |context, (index, element)| {
+                context.validate_at_with(index, element, JsonInvalidity::Invalid)
+            });
+        Err((None, context))
+    }
+}
+
+/// Decodes the fields of a JSON object one by one
+///
+/// Invalidities of fields are recorded at the field name.
+pub struct JsonObject<'a, T>
+where
+    T: Validate,
+{
+    object: &'a Map<String, Value>,
+    structure: Vec<Entry<JsonInvalidity<T::Invalidity>>>,
+    /// Semantic invalidities of the decoded fields, only needed
+    /// if the whole object could not be decoded
+    fields: Vec<Entry<JsonInvalidity<T::Invalidity>>>,
+}
+
+impl<T> fmt::Debug for JsonObject<'_, T>
+where
+    T: Validate,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonObject")
+            .field("object", &self.object)
+            .field("structure", &self.structure)
+            .field("fields", &self.fields)
+            .finish()
+    }
+}
+
+impl<'a, T> JsonObject<'a, T>
+where
+    T: Validate,
+{
+    /// Start decoding an object
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the value is not an object.
+    pub fn new(value: &'a Value) -> Result<Self, (Option<T>, JsonContext<T>)> {
+        match value {
+            Value::Object(object) => Ok(Self {
+                object,
+                structure: Vec::new(),
+                fields: Vec::new(),
+            }),
+            value => Err((
+                None,
+                Context::new().invalidate(JsonInvalidity::unexpected("an object", value)),
+            )),
+        }
+    }
+
+    fn take_field(&mut self, name: &'static str) -> Option<&'a Value> {
+        let value = self.object.get(name).filter(|value| !value.is_null());
+        if value.is_none() {
+            self.structure.push(Entry::error(
+                Path::from_iter([name]),
+                JsonInvalidity::Missing,
+            ));
+        }
+        value
+    }
+
+    fn decode<U>(&mut self, path: impl FnOnce() -> Path, value: &Value) -> Option<U>
+    where
+        U: DeserializeOwned,
+    {
+        U::deserialize(value)
+            .map_err(|err| {
+                self.structure.push(Entry::error(
+                    path(),
+                    JsonInvalidity::Malformed(err.to_string()),
+                ));
+            })
+            .ok()
+    }
+
+    /// Decode a required field
+    ///
+    /// Returns `None` if the field is missing or could not be decoded.
+    pub fn field<U>(&mut self, name: &'static str) -> Option<U>
+    where
+        U: DeserializeOwned,
+    {
+        let value = self.take_field(name)?;
+        self.decode(|| Path::from_iter([name]), value)
+    }
+
+    /// Decode an optional field
+    ///
+    /// Absent fields and `null` values are decoded as `Some(None)`.
+    /// Returns `None` if the field could not be decoded.
+    pub fn optional_field<U>(&mut self, name: &'static str) -> Option<Option<U>>
+    where
+        U: DeserializeOwned,
+    {
+        match self.object.get(name) {
+            None | Some(Value::Null) => Some(None),
+            Some(value) => self.decode(|| Path::from_iter([name]), value).map(Some),
+        }
+    }
+
+    /// Decode a required array field element by element
+    ///
+    /// Elements that could not be decoded are recorded at their index.
+    /// Returns `None` if the field is missing or if any of its elements
+    /// could not be decoded.
+    pub fn array_field<U>(&mut self, name: &'static str) -> Option<Vec<U>>
+    where
+        U: DeserializeOwned,
+    {
+        let value = self.take_field(name)?;
+        let Value::Array(array) = value else {
+            self.structure.push(Entry::error(
+                Path::from_iter([name]),
+                JsonInvalidity::unexpected("an array", value),
+            ));
+            return None;
+        };
+        let elements = array
+            .iter()
+            .enumerate()
+            .map(|(index, element)| {
+                self.decode(
+                    || Path::from_iter([PathSegment::field(name), PathSegment::Index(index)]),
+                    element,
+                )
+            })
+            .collect::<Vec<_>>();
+        elements.into_iter().collect()
+    }
+
+    /// Decode a required field and validate it
+    ///
+    /// The decoded value is converted into the field type with `into`.
+    /// The semantic invalidities of the field are mapped into the
+    /// invalidities of the object with `map`. They are only reported
+    /// if the object could not be decoded.
+    pub fn validated_field<D, U>(
+        &mut self,
+        name: &'static str,
+        into: impl FnOnce(D) -> U,
+        map: impl Fn(U::Invalidity) -> T::Invalidity,
+    ) -> Option<U>
+    where
+        D: DeserializeOwned,
+        U: Validate,
+    {
+        let value = self.take_field(name)?;
+        let decoded = into(self.decode(|| Path::from_iter([name]), value)?);
+        self.validate_field(name, &decoded, map);
+        Some(decoded)
+    }
+
+    /// Decode a required field leniently
+    ///
+    /// All structural invalidities of the field are merged into the
+    /// invalidities of the object. The semantic invalidities are
+    /// mapped with `map`. They are only reported if the object could
+    /// not be decoded.
+    pub fn nested_field<U>(
+        &mut self,
+        name: &'static str,
+        map: impl Fn(U::Invalidity) -> T::Invalidity,
+    ) -> Option<U>
+    where
+        U: FromJsonValue,
+    {
+        let value = self.take_field(name)?;
+        let decoded = match U::decode_json_value(value) {
+            Ok(decoded) => decoded,
+            Err((decoded, invalidities)) => {
+                // Includes the semantic invalidities of the decoded
+                // components if the field could not be decoded
+                merge_field(&mut self.structure, name, invalidities, &map);
+                decoded?
+            }
+        };
+        self.validate_field(name, &decoded, map);
+        Some(decoded)
+    }
+
+    /// Validate a decoded field before it is moved into the object
+    ///
+    /// The invalidities are kept until the object is finished.
+    fn validate_field<U>(
+        &mut self,
+        name: &'static str,
+        decoded: &U,
+        map: impl Fn(U::Invalidity) -> T::Invalidity,
+    ) where
+        U: Validate,
+    {
+        if let Err(context) = decoded.validate() {
+            merge_field(
+                &mut self.fields,
+                name,
+                context
+                    .into_iter()
+                    .map(|entry| entry.map(JsonInvalidity::Invalid)),
+                map,
+            );
+        }
+    }
+
+    /// Finish decoding
+    ///
+    /// Only if the object could not be decoded then the invalidities
+    /// of the decoded fields are reported. Otherwise the decoded
+    /// object is supposed to be validated as a whole.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` with the collected invalidities if decoding failed.
+    pub fn finish(self, value: Option<T>) -> JsonDecoded<T> {
+        let Self {
+            object: _,
+            structure,
+            fields,
+        } = self;
+        let mut context = Context::new().merge_exact_size_iter(structure.into_iter());
+        let Some(value) = value else {
+            context = context.merge_exact_size_iter(fields.into_iter());
+            return Err((None, context));
+        };
+        if context.is_empty() {
+            Ok(value)
+        } else {
+            Err((Some(value), context))
+        }
+    }
+}
+
+fn merge_field<U, V>(
+    entries: &mut Vec<Entry<JsonInvalidity<V>>>,
+    name: &'static str,
+    invalidities: impl IntoIterator<Item = Entry<JsonInvalidity<U>>>,
+    map: impl Fn(U) -> V,
+) {
+    entries.extend(invalidities.into_iter().map(|mut entry| {
+        entry.path.prepend(PathSegment::field(name));
+        entry.map(|invalidity| match invalidity {
+            JsonInvalidity::Missing => JsonInvalidity::Missing,
+            JsonInvalidity::Malformed(message) => JsonInvalidity::Malformed(message),
+            JsonInvalidity::Invalid(invalidity) => JsonInvalidity::Invalid(map(invalidity)),
+        })
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use serde_json::json;
+
+    use super::*;
+
+    use crate::{
+        ValidationResult,
+        context::{Severity, Storage},
+    };
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum ContactInvalidity {
+        Email,
+        Phone,
+    }
+
+    #[derive(Debug)]
+    struct Contact {
+        email: String,
+        phone: Option<String>,
+    }
+
+    impl Validate for Contact {
+        type Invalidity = ContactInvalidity;
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            self.validate_within(Context::new()).into()
+        }
+
+        fn validate_within<S>(
+            &self,
+            context: Context<Self::Invalidity, S>,
+        ) -> Context<Self::Invalidity, S>
+        where
+            S: Storage<Self::Invalidity>,
+        {
+            context
+                .invalidate_if(!self.email.contains('@'), ContactInvalidity::Email)
+                .warn_if(
+                    self.phone.as_ref().is_some_and(String::is_empty),
+                    ContactInvalidity::Phone,
+                )
+        }
+    }
+
+    impl FromJsonValue for Contact {
+        fn decode_json_value(value: &Value) -> JsonDecoded<Self> {
+            let mut object = JsonObject::new(value)?;
+            let email = object.field("email");
+            let phone = object.optional_field("phone");
+            let contact = (|| {
+                Some(Self {
+                    email: email?,
+                    phone: phone?,
+                })
+            })();
+            object.finish(contact)
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Age(u8);
+
+    impl Validate for Age {
+        type Invalidity = ();
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            AGE_VALIDATIONS.set(AGE_VALIDATIONS.get() + 1);
+            Context::new().invalidate_if(self.0 < 18, ()).into()
+        }
+    }
+
+    std::thread_local! {
+        static AGE_VALIDATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum CustomerInvalidity {
+        Age,
+        Contact(ContactInvalidity),
+    }
+
+    #[derive(Debug)]
+    struct Customer {
+        age: Age,
+        contact: Contact,
+    }
+
+    impl Validate for Customer {
+        type Invalidity = CustomerInvalidity;
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            self.validate_within(Context::new()).into()
+        }
+
+        fn validate_within<S>(
+            &self,
+            context: Context<Self::Invalidity, S>,
+        ) -> Context<Self::Invalidity, S>
+        where
+            S: Storage<Self::Invalidity>,
+        {
+            context
+                .validate_at_with("age", &self.age, |()| CustomerInvalidity::Age)
+                .validate_at_with("contact", &self.contact, CustomerInvalidity::Contact)
+        }
+    }
+
+    impl FromJsonValue for Customer {
+        fn decode_json_value(value: &Value) -> JsonDecoded<Self> {
+            let mut object = JsonObject::new(value)?;
+            let age = object.validated_field("age", Age, |()| CustomerInvalidity::Age);
+            let contact = object.nested_field("contact", CustomerInvalidity::Contact);
+            let customer = (|| {
+                Some(Self {
+                    age: age?,
+                    contact: contact?,
+                })
+            })();
+            object.finish(customer)
+        }
+    }
+
+    fn report<T: Validate>(result: JsonResult<T>) -> Vec<(String, JsonInvalidity<T::Invalidity>)> {
+        result
+            .err()
+            .map(|(_, context)| {
+                context
+                    .into_iter()
+                    .map(|entry| (entry.path.to_string(), entry.invalidity))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn valid() {
+        let customer = Customer::from_json_value(&json!({
+            "age": 18,
+            "contact": {
+                "email": "a@b",
+                "phone": null,
+            },
+        }))
+        .unwrap()
+        .0;
+        assert_eq!(Age(18), customer.age);
+        assert_eq!(None, customer.contact.phone);
+    }
+
+    #[test]
+    fn valid_with_warnings() {
+        let (customer, warnings) = Customer::from_json_value(&json!({
+            "age": 18,
+            "contact": {
+                "email": "a@b",
+                "phone": "",
+            },
+        }))
+        .unwrap();
+        assert_eq!(Some(String::new()), customer.contact.phone);
+        assert_eq!(
+            vec![Entry {
+                path: Path::from_iter(["contact"]),
+                severity: Severity::Warning,
+                invalidity: JsonInvalidity::Invalid(CustomerInvalidity::Contact(
+                    ContactInvalidity::Phone
+                )),
+            }],
+            warnings.into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn not_an_object() {
+        assert_eq!(
+            vec![(
+                String::new(),
+                JsonInvalidity::Malformed("expected an object, found an array".to_owned())
+            )],
+            report(Customer::from_json_value(&json!([])))
+        );
+    }
+
+    #[test]
+    fn semantic_invalidities_of_decoded_object() {
+        let result = Customer::from_json_value(&json!({
+            "age": 17,
+            "contact": {
+                "email": "a",
+            },
+        }));
+        assert!(matches!(result, Err((Some(_), _))));
+        assert_eq!(
+            vec![
+                (
+                    "age".to_owned(),
+                    JsonInvalidity::Invalid(CustomerInvalidity::Age)
+                ),
+                (
+                    "contact".to_owned(),
+                    JsonInvalidity::Invalid(CustomerInvalidity::Contact(ContactInvalidity::Email))
+                ),
+            ],
+            report(result)
+        );
+    }
+
+    #[test]
+    fn combined_structural_and_semantic_invalidities() {
+        let result = Customer::from_json_value(&json!({
+            "age": 17,
+            "contact": {
+                "phone": 123,
+            },
+        }));
+        assert!(matches!(result, Err((None, _))));
+        let report = report(result);
+        assert_eq!(3, report.len());
+        assert_eq!(
+            ("contact.email".to_owned(), JsonInvalidity::Missing),
+            report[0]
+        );
+        assert_eq!("contact.phone", report[1].0);
+        assert!(matches!(report[1].1, JsonInvalidity::Malformed(_)));
+        assert_eq!(
+            (
+                "age".to_owned(),
+                JsonInvalidity::Invalid(CustomerInvalidity::Age)
+            ),
+            report[2]
+        );
+    }
+
+    #[test]
+    fn semantic_invalidities_of_decoded_fields() {
+        let result = Customer::from_json_value(&json!({
+            "contact": {
+                "email": "a",
+            },
+        }));
+        assert_eq!(
+            vec![
+                ("age".to_owned(), JsonInvalidity::Missing),
+                (
+                    "contact".to_owned(),
+                    JsonInvalidity::Invalid(CustomerInvalidity::Contact(ContactInvalidity::Email))
+                ),
+            ],
+            report(result)
+        );
+    }
+
+    #[test]
+    fn validate_once() {
+        // Once when decoded and once as part of the decoded object
+        AGE_VALIDATIONS.set(0);
+        let result = Customer::from_json_value(&json!({
+            "age": 17,
+            "contact": {
+                "email": "a@b",
+            },
+        }));
+        assert!(matches!(result, Err((Some(_), _))));
+        assert_eq!(2, AGE_VALIDATIONS.get());
+        assert_eq!(
+            vec![(
+                "age".to_owned(),
+                JsonInvalidity::Invalid(CustomerInvalidity::Age)
+            )],
+            report(result)
+        );
+
+        AGE_VALIDATIONS.set(0);
+        let result = Customer::from_json_value(&json!({
+            "age": 17,
+        }));
+        assert!(matches!(result, Err((None, _))));
+        assert_eq!(1, AGE_VALIDATIONS.get());
+    }
+
+    #[derive(Debug)]
+    struct Team {
+        ids: Vec<u8>,
+        members: Vec<Contact>,
+    }
+
+    impl Validate for Team {
+        type Invalidity = ContactInvalidity;
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            Context::new().validate_at("members", &self.members).into()
+        }
+    }
+
+    impl FromJsonValue for Team {
+        fn decode_json_value(value: &Value) -> JsonDecoded<Self> {
+            let mut object = JsonObject::new(value)?;
+            let ids = object.array_field("ids");
+            let members = object.nested_field("members", core::convert::identity);
+            let team = (|| {
+                Some(Self {
+                    ids: ids?,
+                    members: members?,
+                })
+            })();
+            object.finish(team)
+        }
+    }
+
+    #[test]
+    fn arrays() {
+        let team = Team::from_json_value(&json!({
+            "ids": [1, 2],
+            "members": [{ "email": "a@b" }],
+        }))
+        .unwrap()
+        .0;
+        assert_eq!(vec![1, 2], team.ids);
+        assert_eq!(1, team.members.len());
+
+        let result = Team::from_json_value(&json!({
+            "ids": [],
+            "members": [{ "email": "a@b" }, { "email": "a" }],
+        }));
+        assert!(matches!(result, Err((Some(_), _))));
+        assert_eq!(
+            vec![(
+                "members[1]".to_owned(),
+                JsonInvalidity::Invalid(ContactInvalidity::Email)
+            )],
+            report(result)
+        );
+    }
+
+    #[test]
+    fn arrays_element_by_element() {
+        let invalidities = report(Team::from_json_value(&json!({
+            "ids": [1, "2", 300],
+            "members": [{ "email": "a@b" }, { "email": 1 }, { "email": "a" }, {}],
+        })));
+        assert_eq!(
+            vec![
+                "ids[1]",
+                "ids[2]",
+                "members[1].email",
+                "members[3].email",
+                "members[2]"
+            ],
+            invalidities
+                .iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>()
+        );
+        assert!(matches!(invalidities[2].1, JsonInvalidity::Malformed(_)));
+        assert_eq!(JsonInvalidity::Missing, invalidities[3].1);
+        assert_eq!(
+            JsonInvalidity::Invalid(ContactInvalidity::Email),
+            invalidities[4].1
+        );
+        assert_eq!(
+            vec![(
+                "members".to_owned(),
+                JsonInvalidity::Malformed("expected an array, found an object".to_owned())
+            )],
+            report(Team::from_json_value(&json!({
+                "ids": [],
+                "members": {},
+            })))
+        );
+    }
+
+    #[test]
+    fn nested_invalidity() {
+        let invalidity = JsonInvalidity::Invalid(());
+        let invalidity = &invalidity as &dyn NestedInvalidity;
+        assert_eq!(2, invalidity.chain().count());
+        assert!(invalidity.root().is::<()>());
+        assert!(
+            (&JsonInvalidity::<()>::Missing as &dyn NestedInvalidity)
+                .source()
+                .is_none()
+        );
+    }
+}
@@ -9,27 +9,97 @@
 //!
 //! Please refer to the bundled `reservation.rs` example to get an idea of how it works.
 //!
-//! Without any macro magic, at least not now.
+//! No macro magic is needed. Optionally, both `Validate` and the
+//! corresponding invalidity types could be derived by enabling the
+//! `derive` feature.
 
-use core::{any::Any, fmt::Debug, ops::Deref};
+#[cfg(any(feature = "alloc", test))]
+extern crate alloc;
+
+use core::{
+    any::Any,
+    fmt::Debug,
+    ops::{ControlFlow, Deref},
+};
 
 /// Invalidity context
 pub mod context;
-use self::context::Context;
+
+pub mod constraint;
+use self::context::{Context, Storage};
+
+pub mod path;
+use self::path::PathSegment;
+
+#[cfg(feature = "alloc")]
+pub mod map;
+
+pub mod tuple;
 
 mod smallvec;
 
+#[cfg(feature = "heapless")]
+mod heapless;
+
+#[cfg(feature = "serde")]
+mod serde;
+
+#[cfg(feature = "alloc")]
+mod collections;
+
+mod nested;
+pub use self::nested::NestedInvalidity;
+
+#[cfg(feature = "alloc")]
+mod dynamic;
+#[cfg(feature = "alloc")]
+pub use self::dynamic::{AnyContext, DynValidate};
+
+#[cfg(feature = "alloc")]
+mod registry;
+#[cfg(feature = "alloc")]
+pub use self::registry::ValidatorRegistry;
+
+mod validate_with;
+pub use self::validate_with::{ValidateWith, ValidatedFromWith, ValidatedWithResult};
+
+#[cfg(feature = "async")]
+mod async_validate;
+#[cfg(feature = "async")]
+pub use self::async_validate::{AsyncValidate, Ready};
+
+#[cfg(feature = "json")]
+pub mod json;
+
 mod util;
 use self::util::UnitResult;
+pub use self::util::{IsEmpty, Mergeable, MergeableSized};
+
+/// Derive macros for `Validate` and invalidity types
+#[cfg(feature = "derive")]
+pub use semval_derive::{Invalidity, NestedInvalidity, Validate};
+
+/// Not public API, only used by the derive macros
+#[doc(hidden)]
+#[cfg(feature = "regex")]
+pub mod __private {
+    pub use regex::Regex;
+    pub use std::sync::LazyLock;
+}
 
 /// The crate's prelude
 ///
 /// A proposed set of imports to ease usage of this crate.
 pub mod prelude {
     pub use super::{
-        IntoValidated, Invalidity, IsValid, Validate, Validated, ValidatedFrom, ValidatedResult,
-        ValidationResult, context::Context as ValidationContext,
+        IntoValidated, Invalidity, IsValid, NestedInvalidity, Validate, Validated, ValidatedFrom,
+        ValidatedResult, ValidationResult, context::Context as ValidationContext,
     };
+
+    pub use super::{ValidateWith, ValidatedFromWith};
+
+    #[cfg(feature = "async")]
+    pub use super::AsyncValidate;
 }
 
 /// Result of a validation
@@ -40,7 +110,12 @@ pub mod prelude {
 ///
 /// In contrast to common results the actual payload is carried by
 /// the error variant while a successful result is just the unit type.
-pub type ValidationResult<V> = UnitResult<Context<V>>;
+///
+/// The invalidities are stored in the [`DefaultStorage`](context::DefaultStorage)
+/// unless another [`Storage`] is chosen, e.g. by finishing a validation
+/// [within](Validate::validate_within) an
+/// [`InlineContext`](context::InlineContext).
+pub type ValidationResult<V, S = context::DefaultStorage<V>> = UnitResult<Context<V, S>>;
 
 /// Invalidities that cause validation failures
 ///
@@ -55,11 +130,32 @@ pub type ValidationResult<V> = UnitResult<Context<V>>;
 ///
 /// The trait bound `Any` is implicitly implemented for most types and
 /// enables basic type inspection and downcasting for generically handling
-/// validation results though runtime reflection.
+/// validation results though runtime reflection. Nested invalidities are
+/// searched with [`NestedInvalidity`].
 pub trait Invalidity: Any + Debug {}
 
 impl<V> Invalidity for V where V: Any + Debug {}
 
+impl dyn Invalidity {
+    /// Check if the concrete type is `T`
+    #[must_use]
+    pub fn is<T>(&self) -> bool
+    where
+        T: Any,
+    {
+        (self as &dyn Any).is::<T>()
+    }
+
+    /// Downcast into the concrete type `T`
+    #[must_use]
+    pub fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: Any,
+    {
+        (self as &dyn Any).downcast_ref()
+    }
+}
+
 /// A trait for validating types
 ///
 /// Validation is expected to be an expensive operation that should
@@ -76,6 +172,76 @@ pub trait Validate {
     /// Returns `Err` with the collected invalidities if one or more
     /// validations failed.
     fn validate(&self) -> ValidationResult<Self::Invalidity>;
+
+    /// Perform the validation within the given context
+    ///
+    /// Merges all invalidities into the context and returns it. The
+    /// mode and limit of the context must be respected when validating
+    /// components, i.e. by validating them within the same context
+    /// with [`Context::validate()`] and its siblings. Fail-fast
+    /// contexts stop after the first error and bounded contexts
+    /// only count the invalidities that exceed their limit.
+    ///
+    /// The caller chooses the [`Storage`] of the invalidities and
+    /// components are validated within contexts of the
+    /// [nested storage](Storage::Nested).
+    ///
+    /// The default implementation merges the result of
+    /// [`validate()`](Self::validate). Aggregates should implement
+    /// this method and forward `validate()` to it.
+    #[must_use]
+    fn validate_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        if context.is_done() {
+            return context;
+        }
+        context.merge_result(self.validate())
+    }
+
+    /// Perform the validation until the first invalidity is detected
+    ///
+    /// The result contains at least one invalidity if the validation
+    /// failed, but not necessarily all of them. Validates within a
+    /// [fail-fast](Context::fail_fast) context.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` with the first invalidities if the validation failed.
+    fn validate_fail_fast(&self) -> ValidationResult<Self::Invalidity> {
+        self.validate_within(Context::<Self::Invalidity>::fail_fast())
+            .into()
+    }
+
+    /// Perform the validation and store at most `limit` invalidities
+    ///
+    /// All other invalidities are only counted as
+    /// [omitted](Context::omitted). Validates within a
+    /// [bounded](Context::bounded) context.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` with at most `limit` invalidities if the validation
+    /// failed.
+    fn validate_bounded(&self, limit: usize) -> ValidationResult<Self::Invalidity> {
+        self.validate_within(Context::<Self::Invalidity>::bounded(limit))
+            .into()
+    }
+
+    /// Count the errors without collecting the invalidities
+    ///
+    /// Warnings and infos are not counted. Validates within a
+    /// [counting](Context::counting) context. Only implementations of
+    /// [`validate_within()`](Self::validate_within) that record their
+    /// invalidities within the given context avoid collecting them.
+    fn count_invalidities(&self) -> usize {
+        self.validate_within(Context::<Self::Invalidity>::counting())
+            .count_errors()
+    }
 }
 
 /// A utility trait for boolean validity checks.
@@ -85,12 +251,15 @@ pub trait IsValid {
     fn is_valid(&self) -> bool;
 }
 
+/// Stops at the first error without storing any invalidities,
+/// see [`Context::checking()`].
 impl<T> IsValid for T
 where
     T: Validate + ?Sized,
 {
     fn is_valid(&self) -> bool {
-        self.validate().is_ok()
+        self.validate_within(Context::<T::Invalidity>::checking())
+            .is_valid()
     }
 }
 
@@ -105,6 +274,16 @@ where
     fn validate(&self) -> ValidationResult<Self::Invalidity> {
         (*self).validate()
     }
+
+    fn validate_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        (*self).validate_within(context)
+    }
 }
 
 /// Validate `Some` or otherwise implicitly evaluate to `Ok`
@@ -125,9 +304,26 @@ where
             Ok(())
         }
     }
+
+    fn validate_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        if let Some(some) = self {
+            some.validate_within(context)
+        } else {
+            context
+        }
+    }
 }
 
 /// Validate all elements of a slice
+///
+/// The invalidities of each element are recorded at its index.
+/// Stops at the first invalid element in fail-fast mode.
 impl<V> Validate for [V]
 where
     V: Validate,
@@ -135,12 +331,24 @@ where
     type Invalidity = V::Invalidity;
 
     fn validate(&self) -> ValidationResult<Self::Invalidity> {
-        self.iter().fold(Context::new(), Context::validate).into()
+        self.validate_within(Context::new()).into()
+    }
+
+    fn validate_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        validate_elements_within(context, self.iter().enumerate())
     }
 }
 
-#[cfg(feature = "std")]
-impl<V> Validate for Vec<V>
+/// Validate all elements of an array
+///
+/// The invalidities of each element are recorded at its index.
+impl<V, const N: usize> Validate for [V; N]
 where
     V: Validate,
 {
@@ -149,18 +357,42 @@ where
     fn validate(&self) -> ValidationResult<Self::Invalidity> {
         self.as_slice().validate()
     }
+
+    fn validate_within<S>(
+        &self,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        self.as_slice().validate_within(context)
+    }
 }
 
-#[cfg(feature = "std")]
-impl<'a, V> Validate for std::borrow::Cow<'a, V>
+/// Validate elements within the context and record their invalidities
+/// at `segment`
+///
+/// Skips all remaining elements once the context is done.
+pub(crate) fn validate_elements_within<'a, V, S, T>(
+    context: Context<V::Invalidity, T>,
+    elements: impl Iterator<Item = (S, &'a V)>,
+) -> Context<V::Invalidity, T>
 where
-    V: Validate + ToOwned + 'a + ?Sized,
+    V: Validate + 'a,
+    S: Into<PathSegment>,
+    T: Storage<V::Invalidity>,
 {
-    type Invalidity = V::Invalidity;
-
-    fn validate(&self) -> ValidationResult<Self::Invalidity> {
-        self.as_ref().validate()
-    }
+    let mut elements = elements;
+    let (ControlFlow::Continue(context) | ControlFlow::Break(context)) =
+        elements.try_fold(context, |context, (segment, element)| {
+            let context = context.validate_at(segment, element);
+            if context.is_done() {
+                ControlFlow::Break(context)
+            } else {
+                ControlFlow::Continue(context)
+            }
+        });
+    context
 }
 
 /// Tags a type as _validated_
@@ -195,13 +427,19 @@ impl<T> Deref for Validated<T> {
 }
 
 /// Result of a value-to-value conversion with post-validation of the output value
-pub type ValidatedResult<T> =
-    core::result::Result<Validated<T>, (T, Context<<T as Validate>::Invalidity>)>;
+///
+/// On success the validated value is accompanied by a valid context that
+/// contains all warnings and infos, if any.
+pub type ValidatedResult<T> = core::result::Result<
+    (Validated<T>, Context<<T as Validate>::Invalidity>),
+    (T, Context<<T as Validate>::Invalidity>),
+>;
 
 /// Value-to-value conversion with post-validation of the output value
 ///
-/// On success the output value is returned. On validation errors the output
-/// value is returned together with all invalidities.
+/// On success the output value is returned together with all warnings and
+/// infos. On validation errors the output value is returned together with
+/// all invalidities.
 ///
 /// If validation of the output value has failed clients
 ///  - may discard the output and abort,
@@ -248,7 +486,7 @@ pub type ValidatedResult<T> =
 ///
 /// let email = Email("test@example.com".to_string());
 /// match Email::validated_from(email) {
-///     Ok(email) => println!("Valid e-mail address: {}", email.0),
+///     Ok((email, _warnings)) => println!("Valid e-mail address: {}", email.0),
 ///     Err((email, context)) => println!("Invalid e-mail address: {} {:?}", email.0, context),
 /// }
 /// ```
@@ -267,10 +505,11 @@ where
     T: Validate,
 {
     fn validated_from(from: T) -> ValidatedResult<Self> {
-        if let Err(err) = from.validate() {
-            Err((from, err))
+        let context = from.validate_within(Context::new());
+        if context.is_valid() {
+            Ok((Validated(from), context))
         } else {
-            Ok(Validated(from))
+            Err((from, context))
         }
     }
 }
@@ -303,6 +542,8 @@ where
 mod tests {
     use super::*;
 
+    use alloc::{vec, vec::Vec};
+
     struct AlwaysValid;
 
     impl Validate for AlwaysValid {
@@ -405,7 +646,20 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "std")]
+    fn validate_slices_paths() {
+        assert_eq!(
+            vec![path::Path::from_iter([1]), path::Path::from_iter([2])],
+            [Dummy::valid(), Dummy::invalid(), Dummy::invalid()]
+                .validate()
+                .unwrap_err()
+                .into_iter()
+                .map(|entry| entry.path)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
     fn validate_borrowed_vec() {
         let vec = vec![Dummy::valid(), Dummy::valid()];
         let borrowed_vec = &vec;
@@ -455,9 +709,178 @@ mod tests {
         assert!(IntoValidated::<AlwaysInvalid>::into_validated(AlwaysInvalid).is_err());
     }
 
+    #[test]
+    fn validate_slices_fail_fast() {
+        struct Counted<'a>(&'a core::cell::Cell<usize>, Dummy);
+
+        impl Validate for Counted<'_> {
+            type Invalidity = ();
+
+            fn validate(&self) -> ValidationResult<Self::Invalidity> {
+                self.0.set(self.0.get() + 1);
+                self.1.validate()
+            }
+        }
+
+        let count = core::cell::Cell::new(0);
+        let elements = [
+            Counted(&count, Dummy::valid()),
+            Counted(&count, Dummy::invalid()),
+            Counted(&count, Dummy::invalid()),
+        ];
+        assert!(!elements.is_valid());
+        assert_eq!(2, count.get());
+        assert_eq!(
+            vec![path::Path::from_iter([1])],
+            elements
+                .validate_fail_fast()
+                .unwrap_err()
+                .into_iter()
+                .map(|entry| entry.path)
+                .collect::<Vec<_>>()
+        );
+        count.set(0);
+        assert_eq!(2, elements.validate().unwrap_err().into_iter().count());
+        assert_eq!(3, count.get());
+    }
+
+    struct Tiny(bool);
+
+    impl Validate for Tiny {
+        type Invalidity = ();
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            self.validate_within(Context::new()).into()
+        }
+
+        fn validate_within<S>(
+            &self,
+            context: Context<Self::Invalidity, S>,
+        ) -> Context<Self::Invalidity, S>
+        where
+            S: Storage<Self::Invalidity>,
+        {
+            context.invalidate_if(!self.0, ())
+        }
+    }
+
+    #[test]
+    fn validate_with_inline_capacity() {
+        assert!(Tiny(true).is_valid());
+        let result: ValidationResult<_, context::InlineStorage<_, 1>> =
+            Tiny(false).validate_within(Default::default()).into();
+        assert_eq!(1, result.unwrap_err().len());
+
+        // Nested validations are performed within the same storage
+        let elements = [[Tiny(false), Tiny(true)], [Tiny(false), Tiny(false)]];
+        let context: context::InlineContext<_, 32> = elements.validate_within(Default::default());
+        assert_eq!(3, context.len());
+    }
+
+    struct Unusual;
+
+    impl Validate for Unusual {
+        type Invalidity = ();
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            self.validate_within(Context::new()).into()
+        }
+
+        fn validate_within<S>(
+            &self,
+            context: Context<Self::Invalidity, S>,
+        ) -> Context<Self::Invalidity, S>
+        where
+            S: Storage<Self::Invalidity>,
+        {
+            context.warn(())
+        }
+    }
+
+    #[test]
+    fn validated_from_with_warnings() {
+        let Ok((_, warnings)) = Unusual::validated_from(Unusual) else {
+            panic!();
+        };
+        assert!(warnings.is_valid());
+        assert_eq!(1, warnings.into_iter().count());
+        let Ok((_, warnings)) = AlwaysValid::validated_from(AlwaysValid) else {
+            panic!();
+        };
+        assert_eq!(0, warnings.into_iter().count());
+        assert!([Unusual, Unusual].validate_fail_fast().is_ok());
+        // Warnings are reported within a context and don't stop fail-fast validations
+        assert_eq!(
+            2,
+            [Unusual, Unusual]
+                .validate_within(Context::<()>::fail_fast())
+                .len()
+        );
+    }
+
+    #[test]
+    fn validate_bounded() {
+        struct Probe<'a>(&'a core::cell::RefCell<Vec<Option<usize>>>);
+
+        impl Validate for Probe<'_> {
+            type Invalidity = ();
+
+            fn validate(&self) -> ValidationResult<Self::Invalidity> {
+                self.validate_within(Context::new()).into()
+            }
+
+            fn validate_within<S>(
+                &self,
+                context: Context<Self::Invalidity, S>,
+            ) -> Context<Self::Invalidity, S>
+            where
+                S: Storage<Self::Invalidity>,
+            {
+                self.0.borrow_mut().push(context.limit());
+                context.invalidate(())
+            }
+        }
+
+        let limits = core::cell::RefCell::new(Vec::new());
+        let probes = [Probe(&limits), Probe(&limits), Probe(&limits)];
+        let context = probes.validate_bounded(2).unwrap_err();
+        assert_eq!((2, 1), (context.len(), context.omitted()));
+        assert_eq!(vec![Some(2), Some(1), Some(0)], limits.take());
+
+        let nested = [
+            [AlwaysInvalid, AlwaysInvalid],
+            [AlwaysInvalid, AlwaysInvalid],
+        ];
+        let context = nested.validate_bounded(3).unwrap_err();
+        assert_eq!((3, 1), (context.len(), context.omitted()));
+        assert_eq!(4, context.count_errors());
+    }
+
+    #[test]
+    fn count_invalidities() {
+        assert_eq!(0, AlwaysValid.count_invalidities());
+        assert_eq!(1, AlwaysInvalid.count_invalidities());
+        assert_eq!(0, Unusual.count_invalidities());
+        assert_eq!(0, None::<AlwaysInvalid>.count_invalidities());
+        assert_eq!(
+            2,
+            [Dummy::invalid(), Dummy::valid(), Dummy::invalid()].count_invalidities()
+        );
+        assert_eq!(
+            3,
+            Context::<()>::counting()
+                .invalidate(())
+                .validate(&[AlwaysInvalid, AlwaysInvalid].as_slice())
+                .validate(&Unusual)
+                .count_errors()
+        );
+    }
+
     #[test]
     fn is_valid() {
         assert!(AlwaysValid.is_valid());
         assert!(!AlwaysInvalid.is_valid());
+        assert!(Unusual.is_valid());
+        assert!([Unusual].is_valid());
     }
 }
@@ -0,0 +1,388 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Validation of maps with keys and values
+//!
+//! The implementations of [`Validate`] for maps only validate the
+//! values. Maps with keys that need to be validated, e.g. locale tags
+//! of translations, are validated with [`Context::validate_map()`].
+
+use alloc::string::ToString as _;
+use core::fmt::{self, Debug, Display};
+
+use crate::{
+    Invalidity, NestedInvalidity, Validate,
+    context::{Context, Storage},
+    path::PathSegment,
+};
+
+/// Invalidities of a map entry
+///
+/// Distinguishes an invalid key from an invalid value and carries
+/// a clone of the affected key.
+pub enum MapInvalidity<K, V>
+where
+    K: Validate,
+    V: Validate,
+{
+    /// The key is invalid
+    Key {
+        /// The invalid key
+        key: K,
+
+        /// The invalidity of the key
+        invalidity: K::Invalidity,
+    },
+
+    /// The value is invalid
+    Value {
+        /// The key of the invalid value
+        key: K,
+
+        /// The invalidity of the value
+        invalidity: V::Invalidity,
+    },
+}
+
+impl<K, V> MapInvalidity<K, V>
+where
+    K: Validate,
+    V: Validate,
+{
+    /// The key of the invalid entry
+    #[must_use]
+    pub const fn key(&self) -> &K {
+        match self {
+            Self::Key { key, .. } | Self::Value { key, .. } => key,
+        }
+    }
+}
+
+// The following traits are implemented manually, because the values
+// themselves are not required to implement them.
+
+impl<K, V> Debug for MapInvalidity<K, V>
+where
+    K: Validate + Debug,
+    V: Validate,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key { key, invalidity } => f
+                .debug_struct("Key")
+                .field("key", key)
+                .field("invalidity", invalidity)
+                .finish(),
+            Self::Value { key, invalidity } => f
+                .debug_struct("Value")
+                .field("key", key)
+                .field("invalidity", invalidity)
+                .finish(),
+        }
+    }
+}
+
+impl<K, V> Clone for MapInvalidity<K, V>
+where
+    K: Validate + Clone,
+    V: Validate,
+    K::Invalidity: Clone,
+    V::Invalidity: Clone,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Self::Key { key, invalidity } => Self::Key {
+                key: key.clone(),
+                invalidity: invalidity.clone(),
+            },
+            Self::Value { key, invalidity } => Self::Value {
+                key: key.clone(),
+                invalidity: invalidity.clone(),
+            },
+        }
+    }
+}
+
+impl<K, V> PartialEq for MapInvalidity<K, V>
+where
+    K: Validate + PartialEq,
+    V: Validate,
+    K::Invalidity: PartialEq,
+    V::Invalidity: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::Key { key, invalidity },
+                Self::Key {
+                    key: other_key,
+                    invalidity: other_invalidity,
+                },
+            ) => key == other_key && invalidity == other_invalidity,
+            (
+                Self::Value { key, invalidity },
+                Self::Value {
+                    key: other_key,
+                    invalidity: other_invalidity,
+                },
+            ) => key == other_key && invalidity == other_invalidity,
+            _ => false,
+        }
+    }
+}
+
+impl<K, V> Eq for MapInvalidity<K, V>
+where
+    K: Validate + Eq,
+    V: Validate,
+    K::Invalidity: Eq,
+    V::Invalidity: Eq,
+{
+}
+
+/// Exposes the invalidity of the key or value
+impl<K, V> NestedInvalidity for MapInvalidity<K, V>
+where
+    K: Validate + Debug + 'static,
+    V: Validate + 'static,
+    K::Invalidity: NestedInvalidity,
+    V::Invalidity: NestedInvalidity,
+{
+    fn source(&self) -> Option<&dyn NestedInvalidity> {
+        match self {
+            Self::Key { invalidity, .. } => Some(invalidity),
+            Self::Value { invalidity, .. } => Some(invalidity),
+        }
+    }
+}
+
+/// The key of a map entry that is recorded as a path segment
+///
+/// Implemented for all types that implement `Display`. Other keys,
+/// e.g. tuples, could implement this trait directly.
+pub trait MapKey {
+    /// The path segment of the entry with this key
+    fn to_path_segment(&self) -> PathSegment;
+}
+
+impl<K> MapKey for K
+where
+    K: Display + ?Sized,
+{
+    fn to_path_segment(&self) -> PathSegment {
+        PathSegment::key(self.to_string())
+    }
+}
+
+/// The key of a map entry that is converted into a path segment
+///
+/// Defers the allocation of the key until an invalidity is
+/// recorded at it.
+pub(crate) struct KeySegment<'a, K>(pub(crate) &'a K)
+where
+    K: ?Sized;
+
+impl<K> From<KeySegment<'_, K>> for PathSegment
+where
+    K: MapKey + ?Sized,
+{
+    fn from(from: KeySegment<'_, K>) -> Self {
+        from.0.to_path_segment()
+    }
+}
+
+impl<V, S> Context<V, S>
+where
+    V: Invalidity,
+    S: Storage<V>,
+{
+    /// Validate both keys and values of a map and merge the results
+    /// into this context
+    ///
+    /// The invalidities of each entry are recorded at its key.
+    #[inline]
+    #[must_use]
+    pub fn validate_map<'a, K, T>(self, entries: impl IntoIterator<Item = (&'a K, &'a T)>) -> Self
+    where
+        K: Validate + Clone + MapKey + 'a,
+        T: Validate + 'a,
+        MapInvalidity<K, T>: Into<V>,
+    {
+        self.validate_map_with(entries, Into::into)
+    }
+
+    /// Validate both keys and values of a map and merge the mapped
+    /// results into this context
+    ///
+    /// The invalidities of each entry are recorded at its key.
+    #[must_use]
+    pub fn validate_map_with<'a, F, K, T>(
+        self,
+        entries: impl IntoIterator<Item = (&'a K, &'a T)>,
+        map: F,
+    ) -> Self
+    where
+        F: Fn(MapInvalidity<K, T>) -> V,
+        K: Validate + Clone + MapKey + 'a,
+        T: Validate + 'a,
+    {
+        entries.into_iter().fold(self, |context, (key, value)| {
+            context.scoped(KeySegment(key), |context| {
+                context
+                    .validate_with(key, |invalidity| {
+                        map(MapInvalidity::Key {
+                            key: key.clone(),
+                            invalidity,
+                        })
+                    })
+                    .validate_with(value, |invalidity| {
+                        map(MapInvalidity::Value {
+                            key: key.clone(),
+                            invalidity,
+                        })
+                    })
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+
+    use crate::ValidationResult;
+
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+    struct Locale(&'static str);
+
+    impl Display for Locale {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.0)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum LocaleInvalidity {
+        Tag,
+    }
+
+    impl Validate for Locale {
+        type Invalidity = LocaleInvalidity;
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            Context::new()
+                .invalidate_if(
+                    self.0.len() != 2 || !self.0.bytes().all(|b| b.is_ascii_lowercase()),
+                    LocaleInvalidity::Tag,
+                )
+                .into()
+        }
+    }
+
+    struct Text(String);
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum TextInvalidity {
+        Empty,
+    }
+
+    impl Validate for Text {
+        type Invalidity = TextInvalidity;
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            Context::new()
+                .invalidate_if(self.0.is_empty(), TextInvalidity::Empty)
+                .into()
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum TranslationsInvalidity {
+        Locale(Locale, LocaleInvalidity),
+        Text(Locale, TextInvalidity),
+    }
+
+    impl From<MapInvalidity<Locale, Text>> for TranslationsInvalidity {
+        fn from(from: MapInvalidity<Locale, Text>) -> Self {
+            match from {
+                MapInvalidity::Key { key, invalidity } => Self::Locale(key, invalidity),
+                MapInvalidity::Value { key, invalidity } => Self::Text(key, invalidity),
+            }
+        }
+    }
+
+    fn translations() -> BTreeMap<Locale, Text> {
+        BTreeMap::from([
+            (Locale("de"), Text("Hallo".into())),
+            (Locale("EN"), Text("Hello".into())),
+            (Locale("fr"), Text(String::new())),
+            (Locale("x"), Text(String::new())),
+        ])
+    }
+
+    #[test]
+    fn validate_map() {
+        let context = Context::<TranslationsInvalidity>::new().validate_map(&translations());
+        assert_eq!(4, context.count_errors());
+        assert_eq!(
+            concat!(
+                r#"["EN"]: Locale(Locale("EN"), Tag); ["fr"]: Text(Locale("fr"), Empty); "#,
+                r#"["x"]: Locale(Locale("x"), Tag); ["x"]: Text(Locale("x"), Empty)"#,
+            ),
+            context.to_string()
+        );
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+    struct Unrendered;
+
+    impl Display for Unrendered {
+        fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+            unreachable!()
+        }
+    }
+
+    impl Validate for Unrendered {
+        type Invalidity = ();
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn valid_keys_are_not_displayed() {
+        let map = BTreeMap::from([(Unrendered, Text("Hello".into()))]);
+        assert!(map.validate().is_ok());
+        assert!(
+            Context::<MapInvalidity<Unrendered, Text>>::new()
+                .validate_map_with(&map, core::convert::identity)
+                .is_valid()
+        );
+        let map = BTreeMap::from([(Unrendered, Text(String::new()))]);
+        assert_eq!(1, map.count_invalidities());
+    }
+
+    #[test]
+    fn validate_map_with() {
+        let translations = translations();
+        let context = Context::<MapInvalidity<Locale, Text>>::fail_fast()
+            .validate_map_with(&translations, core::convert::identity);
+        assert_eq!(
+            vec![&Locale("EN")],
+            context
+                .iter()
+                .map(|entry| entry.invalidity.key())
+                .collect::<Vec<_>>()
+        );
+        assert!(matches!(
+            context.iter().next().map(|entry| &entry.invalidity),
+            Some(MapInvalidity::Key {
+                invalidity: LocaleInvalidity::Tag,
+                ..
+            })
+        ));
+    }
+}
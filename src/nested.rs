@@ -0,0 +1,279 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Runtime reflection of nested invalidities
+
+#[cfg(feature = "alloc")]
+use alloc::{collections::BTreeMap, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::any::TypeId;
+use core::{any::Any, convert::Infallible, fmt::Debug, iter::successors};
+
+use crate::{
+    Invalidity,
+    constraint::{Custom, Length, NonEmpty, Pattern, Range, Required},
+    context::{Context, Entry, Storage},
+};
+
+/// Reflection of invalidities that wrap the invalidity of a component
+///
+/// Invalidity types commonly wrap the invalidities of subordinate
+/// validations, e.g. `ReservationInvalidity::Customer(CustomerInvalidity)`.
+/// Exposing the wrapped invalidity as its [source](Self::source)
+/// enables to follow the chain of wrappers down to the root cause at
+/// runtime, independent of the concrete types, similar to
+/// `std::error::Error::source()`.
+///
+/// Each level of the chain could be downcasted into its concrete
+/// type. Leaf invalidities that don't wrap another
+/// invalidity only need an empty implementation.
+///
+/// Could be derived with `#[derive(NestedInvalidity)]` if the `derive`
+/// feature is enabled.
+pub trait NestedInvalidity: Invalidity {
+    /// The wrapped invalidity, if any
+    fn source(&self) -> Option<&dyn NestedInvalidity> {
+        None
+    }
+}
+
+impl dyn NestedInvalidity {
+    /// Iterate over the chain of wrappers, starting with this
+    /// invalidity and ending with the root cause
+    pub fn chain(&self) -> impl Iterator<Item = &dyn NestedInvalidity> {
+        successors(Some(self), |level| level.source())
+    }
+
+    /// The innermost invalidity without a source
+    #[must_use]
+    pub fn root(&self) -> &dyn NestedInvalidity {
+        let mut root = self;
+        while let Some(source) = root.source() {
+            root = source;
+        }
+        root
+    }
+
+    /// Check if the concrete type is `T`
+    #[must_use]
+    pub fn is<T>(&self) -> bool
+    where
+        T: Any,
+    {
+        self.as_invalidity().is::<T>()
+    }
+
+    /// Downcast into the concrete type `T`
+    #[must_use]
+    pub fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: Any,
+    {
+        self.as_invalidity().downcast_ref()
+    }
+
+    /// Upcast into a type-erased invalidity
+    const fn as_invalidity(&self) -> &dyn Invalidity {
+        self
+    }
+}
+
+impl NestedInvalidity for () {}
+
+impl NestedInvalidity for Infallible {}
+
+impl NestedInvalidity for Length {}
+
+impl<T> NestedInvalidity for Range<T> where T: Any + Debug {}
+
+impl NestedInvalidity for NonEmpty {}
+
+impl NestedInvalidity for Pattern {}
+
+impl NestedInvalidity for Required {}
+
+impl NestedInvalidity for Custom {}
+
+impl<V> Entry<V>
+where
+    V: NestedInvalidity,
+{
+    /// Iterate over the chain of wrappers of the invalidity
+    pub fn chain(&self) -> impl Iterator<Item = &dyn NestedInvalidity> {
+        (&self.invalidity as &dyn NestedInvalidity).chain()
+    }
+
+    /// The root cause of the invalidity
+    #[must_use]
+    pub fn root(&self) -> &dyn NestedInvalidity {
+        (&self.invalidity as &dyn NestedInvalidity).root()
+    }
+}
+
+impl<V, S> Context<V, S>
+where
+    V: NestedInvalidity,
+    S: Storage<V>,
+{
+    /// Find all collected invalidities of type `T`
+    ///
+    /// Searches through the chain of each collected invalidity and
+    /// yields the outermost level of type `T`.
+    pub fn find<T>(&self) -> impl Iterator<Item = &T>
+    where
+        T: Any,
+    {
+        self.iter()
+            .filter_map(|entry| entry.chain().find_map(<dyn NestedInvalidity>::downcast_ref))
+    }
+
+    /// Check if an invalidity of type `T` has been collected
+    ///
+    /// See also: [`Context::find()`]
+    #[must_use]
+    pub fn contains_kind<T>(&self) -> bool
+    where
+        T: Any,
+    {
+        self.find::<T>().next().is_some()
+    }
+
+    /// Iterate over the chains of all collected invalidities
+    pub fn chains(&self) -> impl Iterator<Item = impl Iterator<Item = &dyn NestedInvalidity>> {
+        self.iter().map(Entry::chain)
+    }
+
+    /// Iterate over the root causes of all collected invalidities
+    pub fn root_causes(&self) -> impl Iterator<Item = &dyn NestedInvalidity> {
+        self.iter().map(Entry::root)
+    }
+
+    /// Group the collected invalidities by the type of their root cause
+    #[must_use]
+    #[cfg(feature = "alloc")]
+    pub fn group_by_root(&self) -> BTreeMap<TypeId, Vec<&Entry<V>>> {
+        self.iter().fold(BTreeMap::new(), |mut groups, entry| {
+            let type_id = (entry.root() as &dyn Any).type_id();
+            groups.entry(type_id).or_insert_with(Vec::new).push(entry);
+            groups
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{vec, vec::Vec};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum EmailAddressInvalidity {
+        MinLength,
+        Format,
+    }
+
+    impl NestedInvalidity for EmailAddressInvalidity {}
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum ContactDataInvalidity {
+        Email(EmailAddressInvalidity),
+        Incomplete,
+    }
+
+    impl NestedInvalidity for ContactDataInvalidity {
+        fn source(&self) -> Option<&dyn NestedInvalidity> {
+            match self {
+                Self::Email(inner) => Some(inner),
+                Self::Incomplete => None,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum CustomerInvalidity {
+        NameEmpty,
+        ContactData(ContactDataInvalidity),
+    }
+
+    impl NestedInvalidity for CustomerInvalidity {
+        fn source(&self) -> Option<&dyn NestedInvalidity> {
+            match self {
+                Self::NameEmpty => None,
+                Self::ContactData(inner) => Some(inner),
+            }
+        }
+    }
+
+    #[test]
+    fn find() {
+        let context = Context::<CustomerInvalidity>::new()
+            .invalidate(CustomerInvalidity::NameEmpty)
+            .invalidate_at(
+                "email",
+                CustomerInvalidity::ContactData(ContactDataInvalidity::Email(
+                    EmailAddressInvalidity::Format,
+                )),
+            )
+            .invalidate(CustomerInvalidity::ContactData(
+                ContactDataInvalidity::Incomplete,
+            ))
+            .invalidate(CustomerInvalidity::ContactData(
+                ContactDataInvalidity::Email(EmailAddressInvalidity::MinLength),
+            ));
+        assert_eq!(
+            vec![
+                &EmailAddressInvalidity::Format,
+                &EmailAddressInvalidity::MinLength
+            ],
+            context.find::<EmailAddressInvalidity>().collect::<Vec<_>>()
+        );
+        assert_eq!(3, context.find::<ContactDataInvalidity>().count());
+        assert_eq!(4, context.find::<CustomerInvalidity>().count());
+        assert!(context.contains_kind::<EmailAddressInvalidity>());
+        assert!(!context.contains_kind::<()>());
+    }
+
+    #[test]
+    fn chain_and_root() {
+        let context = Context::<CustomerInvalidity>::new()
+            .invalidate(CustomerInvalidity::NameEmpty)
+            .invalidate_at(
+                "email",
+                CustomerInvalidity::ContactData(ContactDataInvalidity::Email(
+                    EmailAddressInvalidity::Format,
+                )),
+            );
+        assert_eq!(
+            vec![1, 3],
+            context.chains().map(Iterator::count).collect::<Vec<_>>()
+        );
+        let roots = context.root_causes().collect::<Vec<_>>();
+        assert!(roots[0].is::<CustomerInvalidity>());
+        assert_eq!(
+            Some(&EmailAddressInvalidity::Format),
+            roots[1].downcast_ref::<EmailAddressInvalidity>()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn group_by_root() {
+        let context = Context::<CustomerInvalidity>::new()
+            .invalidate(CustomerInvalidity::NameEmpty)
+            .invalidate_at(
+                "email",
+                CustomerInvalidity::ContactData(ContactDataInvalidity::Email(
+                    EmailAddressInvalidity::Format,
+                )),
+            );
+        let groups = context.group_by_root();
+        assert_eq!(2, groups.len());
+        assert_eq!(
+            vec![&crate::path::Path::from_iter(["email"])],
+            groups[&TypeId::of::<EmailAddressInvalidity>()]
+                .iter()
+                .map(|entry| &entry.path)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,534 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Locations of invalidities within nested data structures
+//!
+//! Each invalidity that is recorded in a [`Context`](crate::context::Context)
+//! carries the path from the root of the validated value down to the
+//! component that has been considered invalid.
+
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::fmt::{self, Write as _};
+#[cfg(feature = "alloc")]
+use core::str::FromStr;
+
+use smallvec::SmallVec;
+
+/// The name of a field or the key of a map entry
+///
+/// Names could only be borrowed from static strings if the `alloc`
+/// feature is disabled.
+#[cfg(feature = "alloc")]
+pub type Name = Cow<'static, str>;
+
+/// The name of a field or the key of a map entry
+///
+/// Names could only be borrowed from static strings if the `alloc`
+/// feature is disabled.
+#[cfg(not(feature = "alloc"))]
+pub type Name = &'static str;
+
+/// The number of segments that are stored inline
+///
+/// Longer paths allocate memory on the heap.
+pub const INLINE_PATH_LEN: usize = 4;
+
+/// A single step from a value into one of its components
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// A named field of a struct or struct variant
+    Field(Name),
+
+    /// The position of an element in a sequence
+    Index(usize),
+
+    /// The key of an entry in a map
+    Key(Name),
+}
+
+impl PathSegment {
+    /// A named field
+    #[must_use]
+    pub fn field(name: impl Into<Name>) -> Self {
+        Self::Field(name.into())
+    }
+
+    /// The key of a map entry
+    #[must_use]
+    pub fn key(key: impl Into<Name>) -> Self {
+        Self::Key(key.into())
+    }
+}
+
+impl From<&'static str> for PathSegment {
+    fn from(from: &'static str) -> Self {
+        Self::field(from)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<String> for PathSegment {
+    fn from(from: String) -> Self {
+        Self::field(from)
+    }
+}
+
+impl From<usize> for PathSegment {
+    fn from(from: usize) -> Self {
+        Self::Index(from)
+    }
+}
+
+/// A sequence of segments, starting at the root
+///
+/// The empty path refers to the validated value itself. Paths with
+/// up to [`INLINE_PATH_LEN`] segments don't allocate memory on the heap.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Path {
+    segments: SmallVec<[PathSegment; INLINE_PATH_LEN]>,
+}
+
+impl Path {
+    /// The empty path
+    #[must_use]
+    pub const fn root() -> Self {
+        Self {
+            segments: SmallVec::new_const(),
+        }
+    }
+
+    /// Check if this is the empty path
+    #[must_use]
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// All segments, starting at the root
+    #[must_use]
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Append a segment
+    pub fn push(&mut self, segment: impl Into<PathSegment>) {
+        self.segments.push(segment.into());
+    }
+
+    /// Insert a segment at the root
+    pub(crate) fn prepend(&mut self, segment: PathSegment) {
+        self.segments.insert(0, segment);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<Vec<PathSegment>> for Path {
+    fn from(segments: Vec<PathSegment>) -> Self {
+        Self {
+            segments: SmallVec::from_vec(segments),
+        }
+    }
+}
+
+impl<S> FromIterator<S> for Path
+where
+    S: Into<PathSegment>,
+{
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        Self {
+            segments: iter.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Dotted notation, e.g. `customer.orders[3].qty` or `prices["EUR"]`
+///
+/// Keys are quoted and escaped like string literals. The root path
+/// renders as an empty string. Field names must not contain any of
+/// the characters `.`, `[`, or `]` to be parsed back unambiguously.
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (position, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Field(name) => {
+                    if position > 0 {
+                        f.write_char('.')?;
+                    }
+                    f.write_str(name)?;
+                }
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+                PathSegment::Key(key) => {
+                    f.write_str("[\"")?;
+                    for c in key.chars() {
+                        if matches!(c, '"' | '\\') {
+                            f.write_char('\\')?;
+                        }
+                        f.write_char(c)?;
+                    }
+                    f.write_str("\"]")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parse the dotted notation
+///
+/// The inverse of the `Display` implementation.
+#[cfg(feature = "alloc")]
+impl FromStr for Path {
+    type Err = ParsePathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = SmallVec::new();
+        let mut chars = s.char_indices().peekable();
+        while let Some((position, c)) = chars.next() {
+            match c {
+                '[' => {
+                    let segment = if chars.next_if(|&(_, c)| c == '"').is_some() {
+                        let mut key = String::new();
+                        loop {
+                            match chars.next() {
+                                Some((_, '"')) => break,
+                                Some((_, '\\')) => match chars.next() {
+                                    Some((_, c @ ('"' | '\\'))) => key.push(c),
+                                    Some((position, _)) => {
+                                        return Err(ParsePathError::InvalidEscape { position });
+                                    }
+                                    None => return Err(ParsePathError::UnexpectedEnd),
+                                },
+                                Some((_, c)) => key.push(c),
+                                None => return Err(ParsePathError::UnexpectedEnd),
+                            }
+                        }
+                        PathSegment::key(key)
+                    } else {
+                        let start = position + 1;
+                        let mut end = start;
+                        while let Some((_, c)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                            end += c.len_utf8();
+                        }
+                        let index = s[start..end]
+                            .parse()
+                            .map_err(|_| ParsePathError::InvalidIndex { position: start })?;
+                        PathSegment::Index(index)
+                    };
+                    match chars.next() {
+                        Some((_, ']')) => (),
+                        Some((position, _)) => {
+                            return Err(ParsePathError::UnexpectedChar { position });
+                        }
+                        None => return Err(ParsePathError::UnexpectedEnd),
+                    }
+                    segments.push(segment);
+                }
+                '.' if segments.is_empty() => {
+                    return Err(ParsePathError::UnexpectedChar { position });
+                }
+                ']' => return Err(ParsePathError::UnexpectedChar { position }),
+                // Fields after the first segment must be separated by '.'
+                c if c != '.' && !segments.is_empty() => {
+                    return Err(ParsePathError::UnexpectedChar { position });
+                }
+                _ => {
+                    let start = if c == '.' { position + 1 } else { position };
+                    let mut end = position + c.len_utf8();
+                    while let Some((_, c)) = chars.next_if(|&(_, c)| !matches!(c, '.' | '[' | ']'))
+                    {
+                        end += c.len_utf8();
+                    }
+                    if start == end {
+                        return Err(ParsePathError::UnexpectedChar { position: start });
+                    }
+                    segments.push(PathSegment::field(String::from(&s[start..end])));
+                }
+            }
+        }
+        Ok(Self { segments })
+    }
+}
+
+impl Path {
+    /// Render as a JSON Pointer according to RFC 6901
+    ///
+    /// Both fields and keys are rendered as member names. The root
+    /// path renders as an empty string.
+    #[must_use]
+    pub const fn json_pointer(&self) -> JsonPointer<'_> {
+        JsonPointer(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Path {
+    /// Parse a JSON Pointer according to RFC 6901
+    ///
+    /// JSON Pointers don't distinguish between member names and
+    /// array indices. Reference tokens that consist only of decimal
+    /// digits without leading zeros are parsed as [`PathSegment::Index`],
+    /// all other tokens as [`PathSegment::Field`].
+    pub fn from_json_pointer(pointer: &str) -> Result<Self, ParsePathError> {
+        if pointer.is_empty() {
+            return Ok(Self::root());
+        }
+        let Some(tokens) = pointer.strip_prefix('/') else {
+            return Err(ParsePathError::UnexpectedChar { position: 0 });
+        };
+        let mut segments = SmallVec::new();
+        let mut position = 1;
+        for token in tokens.split('/') {
+            let is_index = !token.is_empty()
+                && token.bytes().all(|b| b.is_ascii_digit())
+                && (token == "0" || !token.starts_with('0'));
+            let segment = if is_index {
+                token
+                    .parse()
+                    .map(PathSegment::Index)
+                    .map_err(|_| ParsePathError::InvalidIndex { position })?
+            } else {
+                PathSegment::field(unescape_json_pointer_token(token, position)?)
+            };
+            segments.push(segment);
+            position += token.len() + 1;
+        }
+        Ok(Self { segments })
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn unescape_json_pointer_token(token: &str, offset: usize) -> Result<String, ParsePathError> {
+    let mut unescaped = String::with_capacity(token.len());
+    let mut chars = token.char_indices();
+    while let Some((position, c)) = chars.next() {
+        if c != '~' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some((_, '0')) => unescaped.push('~'),
+            Some((_, '1')) => unescaped.push('/'),
+            _ => {
+                return Err(ParsePathError::InvalidEscape {
+                    position: offset + position,
+                });
+            }
+        }
+    }
+    Ok(unescaped)
+}
+
+/// Renders a [`Path`] as a JSON Pointer
+///
+/// Created by [`Path::json_pointer()`].
+#[derive(Debug, Clone, Copy)]
+pub struct JsonPointer<'a>(&'a Path);
+
+impl fmt::Display for JsonPointer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.0.segments {
+            f.write_char('/')?;
+            match segment {
+                PathSegment::Field(token) | PathSegment::Key(token) => {
+                    for c in token.chars() {
+                        match c {
+                            '~' => f.write_str("~0")?,
+                            '/' => f.write_str("~1")?,
+                            c => f.write_char(c)?,
+                        }
+                    }
+                }
+                PathSegment::Index(index) => write!(f, "{index}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Failed to parse a [`Path`]
+///
+/// All positions are byte offsets into the parsed string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParsePathError {
+    /// The input ended unexpectedly
+    UnexpectedEnd,
+
+    /// An unexpected character
+    UnexpectedChar {
+        /// The position of the character
+        position: usize,
+    },
+
+    /// An invalid escape sequence
+    InvalidEscape {
+        /// The position of the escape sequence
+        position: usize,
+    },
+
+    /// An invalid or out of range index
+    InvalidIndex {
+        /// The position of the index
+        position: usize,
+    },
+}
+
+impl fmt::Display for ParsePathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => f.write_str("unexpected end of path"),
+            Self::UnexpectedChar { position } => {
+                write!(f, "unexpected character at position {position}")
+            }
+            Self::InvalidEscape { position } => {
+                write!(f, "invalid escape sequence at position {position}")
+            }
+            Self::InvalidIndex { position } => write!(f, "invalid index at position {position}"),
+        }
+    }
+}
+
+impl core::error::Error for ParsePathError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::ToString as _;
+
+    #[test]
+    fn root() {
+        assert!(Path::root().is_root());
+        assert_eq!(Path::root(), Path::default());
+        assert!(!Path::from_iter(["field"]).is_root());
+    }
+
+    #[test]
+    fn prepend() {
+        let mut path = Path::from_iter([PathSegment::Index(1)]);
+        path.prepend(PathSegment::field("items"));
+        path.push(PathSegment::key("key"));
+        assert_eq!(
+            [
+                PathSegment::field("items"),
+                PathSegment::Index(1),
+                PathSegment::key("key"),
+            ],
+            path.segments()
+        );
+    }
+
+    fn example() -> Path {
+        Path::from_iter([
+            PathSegment::field("customer"),
+            PathSegment::field("orders"),
+            PathSegment::Index(3),
+            PathSegment::key("a/b~c \"d\""),
+            PathSegment::field("qty"),
+        ])
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("", Path::root().to_string());
+        assert_eq!("[0]", Path::from_iter([0]).to_string());
+        assert_eq!(
+            r#"customer.orders[3]["a/b~c \"d\""].qty"#,
+            example().to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn parse() {
+        assert_eq!(Ok(Path::root()), "".parse());
+        assert_eq!(Ok(example()), example().to_string().parse());
+        assert_eq!(
+            Ok(Path::from_iter([
+                PathSegment::Index(1),
+                PathSegment::key("äö"),
+                PathSegment::field("ü")
+            ])),
+            r#"[1]["äö"].ü"#.parse()
+        );
+        assert_eq!(
+            Err(ParsePathError::UnexpectedChar { position: 0 }),
+            ".a".parse::<Path>()
+        );
+        assert_eq!(
+            Err(ParsePathError::UnexpectedChar { position: 2 }),
+            "a..b".parse::<Path>()
+        );
+        assert_eq!(
+            Err(ParsePathError::UnexpectedChar { position: 2 }),
+            "a.".parse::<Path>()
+        );
+        assert_eq!(
+            Err(ParsePathError::InvalidIndex { position: 2 }),
+            "a[]".parse::<Path>()
+        );
+        assert_eq!(
+            Err(ParsePathError::UnexpectedChar { position: 3 }),
+            "a[1.]".parse::<Path>()
+        );
+        assert_eq!(Err(ParsePathError::UnexpectedEnd), "a[1".parse::<Path>());
+        assert_eq!(
+            Err(ParsePathError::UnexpectedChar { position: 3 }),
+            "[0]a".parse::<Path>()
+        );
+        assert_eq!(
+            Err(ParsePathError::UnexpectedEnd),
+            r#"a["b]"#.parse::<Path>()
+        );
+        assert_eq!(
+            Err(ParsePathError::InvalidEscape { position: 4 }),
+            r#"a["\n"]"#.parse::<Path>()
+        );
+    }
+
+    #[test]
+    fn json_pointer() {
+        assert_eq!("", Path::root().json_pointer().to_string());
+        assert_eq!(
+            "/customer/orders/3/a~1b~0c \"d\"/qty",
+            example().json_pointer().to_string()
+        );
+        assert_eq!(
+            "/customer/contact_data/email",
+            Path::from_iter(["customer", "contact_data", "email"])
+                .json_pointer()
+                .to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn parse_json_pointer() {
+        assert_eq!(Ok(Path::root()), Path::from_json_pointer(""));
+        assert_eq!(
+            Ok(Path::from_iter([
+                PathSegment::field("customer"),
+                PathSegment::field("orders"),
+                PathSegment::Index(3),
+                PathSegment::field("a/b~c \"d\""),
+                PathSegment::field("qty"),
+            ])),
+            Path::from_json_pointer(&example().json_pointer().to_string())
+        );
+        assert_eq!(
+            Ok(Path::from_iter([
+                PathSegment::field(""),
+                PathSegment::field("01"),
+                PathSegment::Index(0),
+            ])),
+            Path::from_json_pointer("//01/0")
+        );
+        assert_eq!(
+            Err(ParsePathError::UnexpectedChar { position: 0 }),
+            Path::from_json_pointer("a")
+        );
+        assert_eq!(
+            Err(ParsePathError::InvalidEscape { position: 3 }),
+            Path::from_json_pointer("/a/~2")
+        );
+    }
+}
@@ -0,0 +1,238 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Runtime lookup of validations by type
+
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, btree_map::Entry},
+};
+use core::any::{Any, TypeId, type_name};
+
+use crate::{AnyContext, DynValidate as _, Invalidity, Validate, ValidationResult};
+
+/// A type-erased validation function
+type ValidateFn = fn(&dyn Any, AnyContext) -> AnyContext;
+
+fn validate_any_within<T>(value: &dyn Any, context: AnyContext) -> AnyContext
+where
+    T: Validate + Any,
+{
+    match value.downcast_ref::<T>() {
+        Some(value) => value.validate_dyn_within(context),
+        None => context,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Validator {
+    type_name: &'static str,
+    validate_within: ValidateFn,
+}
+
+/// A registry of validations for types that are only known at runtime
+///
+/// Looks up the validation of a value by its [`TypeId`]. Values are
+/// passed as `&dyn Any` and their invalidities are type-erased, see
+/// [`AnyContext`](crate::AnyContext).
+///
+/// Values that are boxed as `Box<dyn Any>` must be dereferenced
+/// before passing them, otherwise the type of the box is looked up
+/// instead of the type of the value.
+#[derive(Debug, Clone, Default)]
+pub struct ValidatorRegistry {
+    validators: BTreeMap<TypeId, Validator>,
+}
+
+impl ValidatorRegistry {
+    /// Create an empty registry
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            validators: BTreeMap::new(),
+        }
+    }
+
+    /// Register the validation of type `T`
+    ///
+    /// Returns `false` and keeps the existing registration if `T`
+    /// has already been registered.
+    pub fn register<T>(&mut self) -> bool
+    where
+        T: Validate + Any,
+    {
+        let Entry::Vacant(entry) = self.validators.entry(TypeId::of::<T>()) else {
+            return false;
+        };
+        entry.insert(Validator {
+            type_name: type_name::<T>(),
+            validate_within: validate_any_within::<T>,
+        });
+        true
+    }
+
+    /// Check if the type with the given id has been registered
+    #[must_use]
+    pub fn is_registered(&self, type_id: TypeId) -> bool {
+        self.validators.contains_key(&type_id)
+    }
+
+    /// The number of registered types
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.validators.len()
+    }
+
+    /// Check if no types have been registered
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.validators.is_empty()
+    }
+
+    /// Iterate over the ids and names of all registered types
+    ///
+    /// The names are only intended for diagnostic purposes, see
+    /// [`core::any::type_name()`].
+    #[must_use]
+    pub fn registered_types(&self) -> impl ExactSizeIterator<Item = (TypeId, &'static str)> {
+        self.validators
+            .iter()
+            .map(|(type_id, validator)| (*type_id, validator.type_name))
+    }
+
+    /// Validate a value of a registered type
+    ///
+    /// Returns `None` if the type of the value has not been registered.
+    #[must_use]
+    pub fn validate(&self, value: &dyn Any) -> Option<ValidationResult<Box<dyn Invalidity>>> {
+        let validator = self.validators.get(&value.type_id())?;
+        Some((validator.validate_within)(value, AnyContext::new()).into())
+    }
+
+    /// Validate a value of a registered type until the first
+    /// invalidity is detected
+    ///
+    /// Returns `None` if the type of the value has not been registered.
+    #[must_use]
+    pub fn validate_fail_fast(
+        &self,
+        value: &dyn Any,
+    ) -> Option<ValidationResult<Box<dyn Invalidity>>> {
+        let validator = self.validators.get(&value.type_id())?;
+        Some((validator.validate_within)(value, AnyContext::fail_fast()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{vec, vec::Vec};
+
+    use crate::{
+        DynValidate,
+        context::{Context, Storage},
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum QuantityInvalidity {
+        Zero,
+        Max,
+    }
+
+    struct Quantity(u32);
+
+    impl Validate for Quantity {
+        type Invalidity = QuantityInvalidity;
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            self.validate_within(Context::new()).into()
+        }
+
+        fn validate_within<S>(
+            &self,
+            context: Context<Self::Invalidity, S>,
+        ) -> Context<Self::Invalidity, S>
+        where
+            S: Storage<Self::Invalidity>,
+        {
+            context
+                .invalidate_if(self.0 == 0, QuantityInvalidity::Zero)
+                .invalidate_if(self.0 == 0 || self.0 > 10, QuantityInvalidity::Max)
+        }
+    }
+
+    struct Name(&'static str);
+
+    impl Validate for Name {
+        type Invalidity = ();
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            Context::new().invalidate_if(self.0.is_empty(), ()).into()
+        }
+    }
+
+    #[test]
+    fn register() {
+        let mut registry = ValidatorRegistry::new();
+        assert!(registry.is_empty());
+        assert!(registry.register::<Quantity>());
+        assert!(registry.register::<Name>());
+        // Registering twice keeps the existing registration
+        assert!(!registry.register::<Quantity>());
+        assert_eq!(2, registry.len());
+        assert!(registry.validate(&Quantity(0)).unwrap().is_err());
+        assert!(registry.is_registered(TypeId::of::<Name>()));
+        assert!(!registry.is_registered(TypeId::of::<u32>()));
+        let mut type_names = registry
+            .registered_types()
+            .map(|(_, type_name)| type_name)
+            .collect::<Vec<_>>();
+        type_names.sort_unstable();
+        assert_eq!(
+            vec![type_name::<Name>(), type_name::<Quantity>()],
+            type_names
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let mut registry = ValidatorRegistry::default();
+        registry.register::<Quantity>();
+        registry.register::<Name>();
+        assert_eq!(
+            Some(Ok(())),
+            registry.validate(&Name("a")).map(|res| res.map_err(|_| ()))
+        );
+        assert!(registry.validate(&1u32).is_none());
+        let context = registry.validate(&Quantity(0)).unwrap().unwrap_err();
+        assert_eq!(
+            vec![&QuantityInvalidity::Zero, &QuantityInvalidity::Max],
+            context
+                .downcast_iter::<QuantityInvalidity>()
+                .collect::<Vec<_>>()
+        );
+        let context = registry
+            .validate_fail_fast(&Quantity(0))
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(1, context.len());
+        let boxed: Box<dyn Any> = Box::new(Name(""));
+        assert!(registry.validate(&boxed).is_none());
+        assert!(registry.validate(&*boxed).unwrap().is_err());
+    }
+
+    #[test]
+    fn validate_type_erased() {
+        let mut registry = ValidatorRegistry::new();
+        registry.register::<Box<dyn DynValidate>>();
+        let component: Box<dyn DynValidate> = Box::new(Quantity(0));
+        let context = registry.validate(&component).unwrap().unwrap_err();
+        assert_eq!(
+            vec![&QuantityInvalidity::Zero, &QuantityInvalidity::Max],
+            context
+                .downcast_iter::<QuantityInvalidity>()
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,387 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Trait implementations for serde
+//!
+//! A [`Context`] is serialized as a struct with the stored invalidities
+//! and the number of omitted invalidities and errors of a
+//! [bounded](Context::bounded) context, i.e.
+//! `{ "invalidities": [...], "omitted": 3, "omitted_errors": 2 }`.
+//!
+//! Each invalidity is serialized together with its path in dotted
+//! notation and its severity. For human-readable formats the path
+//! is omitted for invalidities of the validated value itself and
+//! the severity is omitted for errors.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, Visitor},
+    ser::SerializeStruct as _,
+};
+
+use crate::{
+    IntoValidated as _, Invalidity, Validate, Validated,
+    context::{Context, Entry, Storage},
+    path::Path,
+    util::MergeableSized as _,
+};
+
+impl Serialize for Path {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Path {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct PathVisitor;
+
+        impl Visitor<'_> for PathVisitor {
+            type Value = Path;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a path in dotted notation")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(PathVisitor)
+    }
+}
+
+/// Path and severity are skipped if they match their defaults
+/// for human-readable formats
+impl<V> Serialize for Entry<V>
+where
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let human_readable = serializer.is_human_readable();
+        let mut state = serializer.serialize_struct("Entry", 3)?;
+        if human_readable && self.path.is_root() {
+            state.skip_field("path")?;
+        } else {
+            state.serialize_field("path", &self.path)?;
+        }
+        if human_readable && self.is_error() {
+            state.skip_field("severity")?;
+        } else {
+            state.serialize_field("severity", &self.severity)?;
+        }
+        state.serialize_field("invalidity", &self.invalidity)?;
+        state.end()
+    }
+}
+
+/// The stored invalidities of a context
+struct Invalidities<'a, V, T>(&'a Context<V, T>)
+where
+    V: Invalidity;
+
+impl<V, T> Serialize for Invalidities<'_, V, T>
+where
+    V: Invalidity + Serialize,
+    T: Storage<V>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.0.iter())
+    }
+}
+
+impl<V, T> Serialize for Context<V, T>
+where
+    V: Invalidity + Serialize,
+    T: Storage<V>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Context", 3)?;
+        state.serialize_field("invalidities", &Invalidities(self))?;
+        state.serialize_field("omitted", &self.omitted())?;
+        state.serialize_field("omitted_errors", &self.omitted_errors())?;
+        state.end()
+    }
+}
+
+/// The deserialized counterpart of a serialized context
+#[derive(Deserialize)]
+#[serde(rename = "Context", deny_unknown_fields)]
+struct Deserialized<V> {
+    invalidities: Vec<Entry<V>>,
+    omitted: usize,
+    omitted_errors: usize,
+}
+
+impl<'de, V, T> Deserialize<'de> for Context<V, T>
+where
+    V: Invalidity + Deserialize<'de>,
+    T: Storage<V>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let Deserialized {
+            invalidities,
+            omitted,
+            omitted_errors,
+        } = Deserialized::<V>::deserialize(deserializer)?;
+        if omitted_errors > omitted {
+            return Err(de::Error::custom(
+                "more omitted errors than omitted invalidities",
+            ));
+        }
+        Ok(Context::default()
+            .merge_exact_size_iter(invalidities.into_iter())
+            .omit(omitted, omitted_errors))
+    }
+}
+
+/// Serialized transparently like the validated value
+impl<T> Serialize for Validated<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+/// Deserialize and validate the value
+///
+/// Fails if the deserialized value is invalid. The error message
+/// lists all invalidities.
+impl<'de, T> Deserialize<'de> for Validated<T>
+where
+    T: Validate + Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)?
+            .into_validated()
+            .map(|(validated, _)| validated)
+            .map_err(|(_, context)| de::Error::custom(format_args!("invalid value: {context}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::{String, ToString as _};
+
+    use crate::{ValidatedFrom as _, ValidationResult};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    enum TestInvalidity {
+        Empty,
+        Length { max: usize },
+    }
+
+    #[test]
+    fn serialize_context() {
+        let context = Context::new()
+            .invalidate(TestInvalidity::Empty)
+            .invalidate_at("name", TestInvalidity::Length { max: 3 })
+            .scoped("items", |context| {
+                context.invalidate_at(1, TestInvalidity::Empty)
+            })
+            .scoped("nickname", |context| context.warn(TestInvalidity::Empty));
+        let json = serde_json::to_value(&context).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "invalidities": [
+                    {
+                        "invalidity": "Empty",
+                    },
+                    {
+                        "path": "name",
+                        "invalidity": {
+                            "Length": {
+                                "max": 3,
+                            },
+                        },
+                    },
+                    {
+                        "path": "items[1]",
+                        "invalidity": "Empty",
+                    },
+                    {
+                        "path": "nickname",
+                        "severity": "warning",
+                        "invalidity": "Empty",
+                    },
+                ],
+                "omitted": 0,
+                "omitted_errors": 0,
+            }),
+            json
+        );
+        assert_eq!(
+            context,
+            serde_json::from_value::<Context<TestInvalidity>>(json).unwrap()
+        );
+    }
+
+    #[test]
+    fn serialize_omitted() {
+        let context = Context::<TestInvalidity>::bounded(1)
+            .invalidate(TestInvalidity::Empty)
+            .warn(TestInvalidity::Empty)
+            .invalidate(TestInvalidity::Empty);
+        let json = serde_json::to_value(&context).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "invalidities": [
+                    {
+                        "invalidity": "Empty",
+                    },
+                ],
+                "omitted": 2,
+                "omitted_errors": 1,
+            }),
+            json
+        );
+        let deserialized = serde_json::from_value::<Context<TestInvalidity>>(json).unwrap();
+        assert_eq!(
+            (1, 2, 2),
+            (
+                deserialized.len(),
+                deserialized.omitted(),
+                deserialized.count_errors()
+            )
+        );
+
+        let context = Context::<TestInvalidity>::bounded(0).invalidate(TestInvalidity::Empty);
+        let json = serde_json::to_value(&context).unwrap();
+        let deserialized = serde_json::from_value::<Context<TestInvalidity>>(json).unwrap();
+        assert!(!deserialized.is_valid());
+
+        assert!(
+            serde_json::from_value::<Context<TestInvalidity>>(serde_json::json!({
+                "invalidities": [],
+                "omitted": 1,
+                "omitted_errors": 2,
+            }))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn serialize_non_self_describing() {
+        let context = Context::<TestInvalidity>::bounded(2)
+            .invalidate(TestInvalidity::Empty)
+            .scoped("items", |context| {
+                context.scoped(1, |context| context.warn(TestInvalidity::Length { max: 3 }))
+            })
+            .invalidate(TestInvalidity::Empty);
+        let bytes = postcard::to_allocvec(&context).unwrap();
+        let deserialized = postcard::from_bytes::<Context<TestInvalidity>>(&bytes).unwrap();
+        assert_eq!(
+            context.iter().collect::<Vec<_>>(),
+            deserialized.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            (1, 2),
+            (deserialized.omitted(), deserialized.count_errors())
+        );
+    }
+
+    #[test]
+    fn deserialize_invalid_path() {
+        let err = serde_json::from_value::<Context<TestInvalidity>>(serde_json::json!({
+            "invalidities": [
+                {
+                    "path": "items[1",
+                    "invalidity": "Empty",
+                },
+            ],
+            "omitted": 0,
+            "omitted_errors": 0,
+        }))
+        .unwrap_err();
+        assert!(err.to_string().contains("unexpected end of path"));
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Name(String);
+
+    impl Validate for Name {
+        type Invalidity = TestInvalidity;
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            Context::new()
+                .invalidate_if(self.0.is_empty(), TestInvalidity::Empty)
+                .invalidate_if(self.0.len() > 3, TestInvalidity::Length { max: 3 })
+                .into()
+        }
+    }
+
+    #[test]
+    fn serialize_validated() {
+        let Ok((validated, _)) = Name::validated_from(Name("abc".to_string())) else {
+            unreachable!();
+        };
+        assert_eq!(
+            serde_json::json!("abc"),
+            serde_json::to_value(validated).unwrap()
+        );
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Request {
+        name: Validated<Name>,
+        names: Option<Vec<Validated<Name>>>,
+    }
+
+    #[test]
+    fn deserialize_validated() {
+        let request = serde_json::from_str::<Request>(r#"{"name":"abc","names":["a"]}"#).unwrap();
+        assert_eq!("abc", request.name.0.0);
+        assert_eq!(
+            1,
+            request.names.map(|names| names.len()).unwrap_or_default()
+        );
+
+        let err = serde_json::from_str::<Request>(r#"{"name":""}"#).unwrap_err();
+        assert!(err.to_string().starts_with("invalid value: Empty at "));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn deserialize_validated_elements() {
+        let err = serde_json::from_str::<Validated<Vec<Name>>>(r#"["a","abcd",""]"#).unwrap_err();
+        assert_eq!(
+            "invalid value: [1]: Length { max: 3 }; [2]: Empty",
+            err.to_string()
+        );
+    }
+}
@@ -0,0 +1,184 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Validation of tuples
+//!
+//! Tuples with up to 6 elements are validated element by element.
+//! The invalidities of each element are recorded at its index and
+//! wrapped into the variant of [`TupleInvalidity`] for its position.
+
+use core::convert::Infallible;
+
+use crate::{
+    NestedInvalidity, Validate, ValidationResult,
+    context::{Context, Storage},
+    path::PathSegment,
+};
+
+/// Combined invalidities of the elements of a tuple
+///
+/// The unused type parameters of shorter tuples default to
+/// [`Infallible`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TupleInvalidity<A, B, C = Infallible, D = Infallible, E = Infallible, F = Infallible> {
+    /// The first element is invalid
+    First(A),
+
+    /// The second element is invalid
+    Second(B),
+
+    /// The third element is invalid
+    Third(C),
+
+    /// The fourth element is invalid
+    Fourth(D),
+
+    /// The fifth element is invalid
+    Fifth(E),
+
+    /// The sixth element is invalid
+    Sixth(F),
+}
+
+/// Exposes the invalidity of the element
+impl<A, B, C, D, E, F> NestedInvalidity for TupleInvalidity<A, B, C, D, E, F>
+where
+    A: NestedInvalidity,
+    B: NestedInvalidity,
+    C: NestedInvalidity,
+    D: NestedInvalidity,
+    E: NestedInvalidity,
+    F: NestedInvalidity,
+{
+    fn source(&self) -> Option<&dyn NestedInvalidity> {
+        match self {
+            Self::First(inner) => Some(inner),
+            Self::Second(inner) => Some(inner),
+            Self::Third(inner) => Some(inner),
+            Self::Fourth(inner) => Some(inner),
+            Self::Fifth(inner) => Some(inner),
+            Self::Sixth(inner) => Some(inner),
+        }
+    }
+}
+
+macro_rules! impl_validate_for_tuple {
+    ($($index:tt: $ty:ident => $variant:ident),+) => {
+        impl<$($ty),+> Validate for ($($ty,)+)
+        where
+            $($ty: Validate,)+
+        {
+            type Invalidity = TupleInvalidity<$(<$ty as Validate>::Invalidity),+>;
+
+            fn validate(&self) -> ValidationResult<Self::Invalidity> {
+                self.validate_within(Context::new()).into()
+            }
+
+            fn validate_within<S>(
+                &self,
+                context: Context<Self::Invalidity, S>,
+            ) -> Context<Self::Invalidity, S>
+            where
+                S: Storage<Self::Invalidity>,
+            {
+                context
+                    $(.validate_at_with(
+                        PathSegment::Index($index),
+                        &self.$index,
+                        TupleInvalidity::$variant,
+                    ))+
+            }
+        }
+    };
+}
+
+impl_validate_for_tuple!(0: A => First, 1: B => Second);
+impl_validate_for_tuple!(0: A => First, 1: B => Second, 2: C => Third);
+impl_validate_for_tuple!(0: A => First, 1: B => Second, 2: C => Third, 3: D => Fourth);
+impl_validate_for_tuple!(
+    0: A => First,
+    1: B => Second,
+    2: C => Third,
+    3: D => Fourth,
+    4: E => Fifth
+);
+impl_validate_for_tuple!(
+    0: A => First,
+    1: B => Second,
+    2: C => Third,
+    3: D => Fourth,
+    4: E => Fifth,
+    5: F => Sixth
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{string::ToString as _, vec, vec::Vec};
+
+    use crate::IsValid as _;
+
+    struct Quantity(u32);
+
+    impl Validate for Quantity {
+        type Invalidity = ();
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            Context::new().invalidate_if(self.0 == 0, ()).into()
+        }
+    }
+
+    struct Name(&'static str);
+
+    impl Validate for Name {
+        type Invalidity = &'static str;
+
+        fn validate(&self) -> ValidationResult<Self::Invalidity> {
+            Context::new()
+                .invalidate_if(self.0.is_empty(), "empty")
+                .into()
+        }
+    }
+
+    #[test]
+    fn pair() {
+        assert!((Name("a"), Quantity(1)).is_valid());
+        let context = (Name(""), Quantity(0)).validate().unwrap_err();
+        assert_eq!(
+            r#"[0]: First("empty"); [1]: Second(())"#,
+            context.to_string()
+        );
+        assert_eq!(
+            vec![TupleInvalidity::First("empty")],
+            (Name(""), Quantity(0))
+                .validate_fail_fast()
+                .unwrap_err()
+                .into_iter()
+                .map(|entry| entry.invalidity)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn nested() {
+        let tuple = (
+            Quantity(1),
+            [Quantity(1), Quantity(0)],
+            (Name(""), Quantity(0)),
+            Name("a"),
+            Quantity(0),
+            Name(""),
+        );
+        assert_eq!(5, tuple.count_invalidities());
+        assert_eq!(
+            vec!["[1][1]", "[2][0]", "[2][1]", "[4]", "[5]"],
+            tuple
+                .validate()
+                .unwrap_err()
+                .iter()
+                .map(|entry| entry.path.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -1,14 +1,19 @@
 // SPDX-FileCopyrightText: slowtec GmbH
 // SPDX-License-Identifier: MPL-2.0
 
-//! Utilities for internal usage
+//! Accumulator traits and utilities
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 
 ///////////////////////////////////////////////////////////////////////////////
 // IsEmpty
 ///////////////////////////////////////////////////////////////////////////////
 
+/// Check if an accumulator contains nothing
 // TODO: Reuse from https://github.com/Stebalien/tool-rs?
-pub(crate) trait IsEmpty {
+pub trait IsEmpty {
+    /// Check if nothing has been accumulated yet
     fn is_empty(&self) -> bool;
 }
 
@@ -31,7 +36,11 @@ impl IsEmpty for usize {
 ///////////////////////////////////////////////////////////////////////////////
 
 /// A monoid for collecting or accumulating items
-pub(crate) trait Mergeable {
+///
+/// Implemented by the storage of a [`Context`](crate::context::Context)
+/// and by quantities for counting.
+pub trait Mergeable {
+    /// The collected or accumulated item
     type Item;
 
     /// Create an empty instance
@@ -44,6 +53,7 @@ pub(crate) trait Mergeable {
         H: Into<Option<usize>>;
 
     /// Consuming combine operation that merges this with another instance
+    #[must_use]
     fn merge(self, other: Self) -> Self;
 
     /// Consuming combine operation that merges this instance with the
@@ -53,13 +63,26 @@ pub(crate) trait Mergeable {
     /// capacity should be reserved for the generated items, e.g. it could
     /// be a preliminary guess or context knowledge how many items the iterator
     /// is supposed to generate.
+    #[must_use]
     fn merge_iter<H, I>(self, reserve_hint: H, iter: I) -> Self
     where
         H: Into<Option<usize>>,
         I: Iterator<Item = Self::Item>;
+
+    /// The maximum number of items that could be collected, if limited
+    ///
+    /// Collections with a fixed capacity must not be merged with more
+    /// items than they could hold. Unlimited by default.
+    fn capacity_limit(&self) -> Option<usize> {
+        None
+    }
 }
 
-pub(crate) trait MergeableSized: Mergeable + Sized {
+/// Extension of [`Mergeable`] for iterators of known length
+pub trait MergeableSized: Mergeable + Sized {
+    /// Merge this instance with the items generated by an iterator,
+    /// reserving capacity for all of them
+    #[must_use]
     fn merge_exact_size_iter<I>(self, iter: I) -> Self
     where
         I: ExactSizeIterator<Item = Self::Item>,
@@ -68,6 +91,8 @@ pub(crate) trait MergeableSized: Mergeable + Sized {
     }
 }
 
+impl<T> MergeableSized for T where T: Mergeable {}
+
 /// Trivial implementation of `Mergeable` for the unit type `()`
 impl Mergeable for () {
     type Item = ();
@@ -99,6 +124,60 @@ impl Mergeable for usize {
     }
 }
 
+///////////////////////////////////////////////////////////////////////////////
+// Vec
+///////////////////////////////////////////////////////////////////////////////
+
+/// Implementation of `IsEmpty` for collections of items
+#[cfg(feature = "alloc")]
+impl<T> IsEmpty for Vec<T> {
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+/// Implementation of `Mergeable` for collecting items
+#[cfg(feature = "alloc")]
+impl<T> Mergeable for Vec<T> {
+    type Item = T;
+
+    fn empty<H>(capacity_hint: H) -> Self
+    where
+        H: Into<Option<usize>>,
+    {
+        let capacity = capacity_hint.into().unwrap_or(0);
+        if capacity > 0 {
+            Self::with_capacity(capacity)
+        } else {
+            Default::default()
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        // Reuse the instance with greater capacity for accumulation (this)
+        // and consume (= drain & drop) the other one (that).
+        let (mut that, mut this) = if self.capacity() < other.capacity() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        this.append(&mut that);
+        this
+    }
+
+    fn merge_iter<H, I>(mut self, reserve_hint: H, iter: I) -> Self
+    where
+        H: Into<Option<usize>>,
+        I: Iterator<Item = Self::Item>,
+    {
+        self.reserve(reserve_hint.into().unwrap_or(0));
+        iter.fold(self, |mut this, item| {
+            this.push(item);
+            this
+        })
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 // UnitResult
 ///////////////////////////////////////////////////////////////////////////////
@@ -141,6 +220,9 @@ where
 mod tests {
     use super::*;
 
+    #[cfg(feature = "alloc")]
+    use alloc::vec;
+
     #[test]
     fn unit_is_empty() {
         assert!(().is_empty());
@@ -151,7 +233,7 @@ mod tests {
     fn unit_mergeable() {
         assert!(() == ().merge(()));
         assert_eq!((), <() as Mergeable>::empty(5));
-        assert_eq!((), ().merge_iter(3, core::iter::repeat(()).take(3)));
+        assert_eq!((), ().merge_iter(3, core::iter::repeat_n((), 3)));
     }
 
     #[test]
@@ -162,62 +244,14 @@ mod tests {
         assert_eq!(Err(3usize), Err(1).merge(Err(2)));
     }
 
-    #[cfg(feature = "std")]
-    impl<T> IsEmpty for Vec<T> {
-        fn is_empty(&self) -> bool {
-            self.is_empty()
-        }
-    }
-
-    #[cfg(feature = "std")]
     #[test]
+    #[cfg(feature = "alloc")]
     fn vec_is_empty() {
         assert!(<Vec<()> as IsEmpty>::is_empty(&vec![]));
     }
 
-    #[cfg(feature = "std")]
-    impl<T> Mergeable for Vec<T> {
-        type Item = T;
-
-        fn empty<H>(capacity_hint: H) -> Self
-        where
-            H: Into<Option<usize>>,
-        {
-            let capacity = capacity_hint.into().unwrap_or(0);
-            if capacity > 0 {
-                Self::with_capacity(capacity)
-            } else {
-                Default::default()
-            }
-        }
-
-        fn merge(self, other: Self) -> Self {
-            // Reuse the instance with greater capacity for accumulation (this)
-            // and consume (= drain & drop) the other one (that).
-            let (mut that, mut this) = if self.capacity() < other.capacity() {
-                (self, other)
-            } else {
-                (other, self)
-            };
-            this.append(&mut that);
-            this
-        }
-
-        fn merge_iter<H, I>(mut self, reserve_hint: H, iter: I) -> Self
-        where
-            H: Into<Option<usize>>,
-            I: Iterator<Item = Self::Item>,
-        {
-            self.reserve(reserve_hint.into().unwrap_or(0));
-            iter.fold(self, |mut this, item| {
-                this.push(item);
-                this
-            })
-        }
-    }
-
-    #[cfg(feature = "std")]
     #[test]
+    #[cfg(feature = "alloc")]
     fn vec_mergeable() {
         assert!(<Vec<()> as Mergeable>::empty(None).is_empty());
         assert!(<Vec<()> as Mergeable>::empty(5).is_empty());
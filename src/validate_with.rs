@@ -0,0 +1,404 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+//! Validation within an external environment
+
+use core::ops::ControlFlow;
+
+use crate::{
+    Invalidity, Validated, ValidationResult,
+    context::{Context, Storage},
+};
+
+/// A trait for validating types within an environment
+///
+/// The environment provides runtime data that is needed for the
+/// validation but that is not part of the validated value, e.g.
+/// the current time or some configuration. Composite types typically
+/// pass the same environment or a part of it on to the validation
+/// of their components.
+pub trait ValidateWith<Env>
+where
+    Env: ?Sized,
+{
+    /// Invalidity objectives
+    type Invalidity: Invalidity;
+
+    /// Perform the validation within the given environment
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` with the collected invalidities if one or more
+    /// validations failed.
+    fn validate_with_env(&self, env: &Env) -> ValidationResult<Self::Invalidity>;
+
+    /// Perform the validation within the given environment and
+    /// an existing context
+    ///
+    /// The counterpart of [`Validate::validate_within()`](crate::Validate::validate_within)
+    /// that passes the mode and the remaining limit of the context
+    /// on to the components. The default implementation skips the
+    /// validation if the context is already done.
+    #[must_use]
+    fn validate_with_env_within<S>(
+        &self,
+        env: &Env,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        if context.is_done() {
+            return context;
+        }
+        context.merge_result(self.validate_with_env(env))
+    }
+}
+
+/// `ValidateWith` is implemented for any reference of a type
+/// that implements `ValidateWith`.
+impl<V, Env> ValidateWith<Env> for &V
+where
+    V: ValidateWith<Env> + ?Sized,
+    Env: ?Sized,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate_with_env(&self, env: &Env) -> ValidationResult<Self::Invalidity> {
+        (*self).validate_with_env(env)
+    }
+
+    fn validate_with_env_within<S>(
+        &self,
+        env: &Env,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        (*self).validate_with_env_within(env, context)
+    }
+}
+
+/// Validate `Some` or otherwise implicitly evaluate to `Ok`
+/// in case of `None`
+impl<V, Env> ValidateWith<Env> for Option<V>
+where
+    V: ValidateWith<Env>,
+    Env: ?Sized,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate_with_env(&self, env: &Env) -> ValidationResult<Self::Invalidity> {
+        if let Some(some) = self {
+            some.validate_with_env(env)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn validate_with_env_within<S>(
+        &self,
+        env: &Env,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        if let Some(some) = self {
+            some.validate_with_env_within(env, context)
+        } else {
+            context
+        }
+    }
+}
+
+/// Validate all elements of a slice within the same environment
+///
+/// The invalidities of each element are recorded at its index.
+impl<V, Env> ValidateWith<Env> for [V]
+where
+    V: ValidateWith<Env>,
+    Env: ?Sized,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate_with_env(&self, env: &Env) -> ValidationResult<Self::Invalidity> {
+        self.validate_with_env_within(env, Context::new()).into()
+    }
+
+    fn validate_with_env_within<S>(
+        &self,
+        env: &Env,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        let (ControlFlow::Continue(context) | ControlFlow::Break(context)) = self
+            .iter()
+            .enumerate()
+            .try_fold(context, |context, (index, element)| {
+                let context = context.scoped(index, |context| context.validate_in(element, env));
+                if context.is_done() {
+                    ControlFlow::Break(context)
+                } else {
+                    ControlFlow::Continue(context)
+                }
+            });
+        context
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V, Env> ValidateWith<Env> for alloc::vec::Vec<V>
+where
+    V: ValidateWith<Env>,
+    Env: ?Sized,
+{
+    type Invalidity = V::Invalidity;
+
+    fn validate_with_env(&self, env: &Env) -> ValidationResult<Self::Invalidity> {
+        self.as_slice().validate_with_env(env)
+    }
+
+    fn validate_with_env_within<S>(
+        &self,
+        env: &Env,
+        context: Context<Self::Invalidity, S>,
+    ) -> Context<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        self.as_slice().validate_with_env_within(env, context)
+    }
+}
+
+impl<V, S> Context<V, S>
+where
+    V: Invalidity,
+    S: Storage<V>,
+{
+    /// Validate the target within an environment and merge the result
+    /// into this context
+    #[inline]
+    #[must_use]
+    pub fn validate_in<U, Env>(
+        self,
+        target: &impl ValidateWith<Env, Invalidity = U>,
+        env: &Env,
+    ) -> Self
+    where
+        U: Invalidity + Into<V>,
+        Env: ?Sized,
+    {
+        self.validate_in_with(target, env, Into::into)
+    }
+
+    /// Validate the target within an environment and merge the mapped
+    /// result into this context
+    #[inline]
+    #[must_use]
+    pub fn validate_in_with<F, U, Env>(
+        self,
+        target: &impl ValidateWith<Env, Invalidity = U>,
+        env: &Env,
+        map: F,
+    ) -> Self
+    where
+        F: Fn(U) -> V,
+        U: Invalidity,
+        Env: ?Sized,
+    {
+        if self.is_done() {
+            return self;
+        }
+        let validated = target.validate_with_env_within(env, self.nested::<U, S::Nested<U>>());
+        self.merge_mapped(validated, |entry| entry.map(&map))
+    }
+}
+
+/// Result of a value-to-value conversion with post-validation of the
+/// output value within an environment
+///
+/// The counterpart of [`ValidatedResult`](crate::ValidatedResult).
+pub type ValidatedWithResult<T, Env> = core::result::Result<
+    (Validated<T>, Context<<T as ValidateWith<Env>>::Invalidity>),
+    (T, Context<<T as ValidateWith<Env>>::Invalidity>),
+>;
+
+/// Value-to-value conversion with post-validation of the output value
+/// within an environment
+///
+/// The counterpart of [`ValidatedFrom`](crate::ValidatedFrom).
+pub trait ValidatedFromWith<T, Env>: ValidateWith<Env> + Sized
+where
+    Env: ?Sized,
+{
+    /// Convert input value into `Self` and validate `self` within
+    /// the given environment
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` with the collected invalidities if one or more
+    /// validations failed.
+    fn validated_from_with(from: T, env: &Env) -> ValidatedWithResult<Self, Env>;
+}
+
+impl<T, Env> ValidatedFromWith<T, Env> for T
+where
+    T: ValidateWith<Env>,
+    Env: ?Sized,
+{
+    fn validated_from_with(from: T, env: &Env) -> ValidatedWithResult<Self, Env> {
+        let context = from.validate_with_env_within(env, Context::new());
+        if context.is_valid() {
+            Ok((Validated(from), context))
+        } else {
+            Err((from, context))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{vec, vec::Vec};
+
+    use crate::{
+        context::{Entry, InlineContext},
+        path::{Path, PathSegment},
+    };
+
+    struct Limits {
+        max_quantity: u32,
+    }
+
+    struct Restaurant {
+        limits: Limits,
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum QuantityInvalidity {
+        Max(u32),
+    }
+
+    struct Quantity(u32);
+
+    impl ValidateWith<Limits> for Quantity {
+        type Invalidity = QuantityInvalidity;
+
+        fn validate_with_env(&self, env: &Limits) -> ValidationResult<Self::Invalidity> {
+            Context::new()
+                .invalidate_if(
+                    self.0 > env.max_quantity,
+                    QuantityInvalidity::Max(env.max_quantity),
+                )
+                .into()
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum ReservationInvalidity {
+        Quantity(QuantityInvalidity),
+    }
+
+    struct Reservation {
+        quantities: Vec<Quantity>,
+    }
+
+    impl ValidateWith<Restaurant> for Reservation {
+        type Invalidity = ReservationInvalidity;
+
+        fn validate_with_env(&self, env: &Restaurant) -> ValidationResult<Self::Invalidity> {
+            self.validate_with_env_within(env, Context::new()).into()
+        }
+
+        fn validate_with_env_within<S>(
+            &self,
+            env: &Restaurant,
+            context: Context<Self::Invalidity, S>,
+        ) -> Context<Self::Invalidity, S>
+        where
+            S: Storage<Self::Invalidity>,
+        {
+            context.scoped("quantities", |context| {
+                context.validate_in_with(
+                    &self.quantities.as_slice(),
+                    &env.limits,
+                    ReservationInvalidity::Quantity,
+                )
+            })
+        }
+    }
+
+    #[test]
+    fn validate_with_env() {
+        let restaurant = Restaurant {
+            limits: Limits { max_quantity: 2 },
+        };
+        assert!(Quantity(2).validate_with_env(&restaurant.limits).is_ok());
+        assert!(
+            Some(Quantity(3))
+                .validate_with_env(&restaurant.limits)
+                .is_err()
+        );
+        let reservation = Reservation {
+            quantities: vec![Quantity(1), Quantity(3)],
+        };
+        assert_eq!(
+            vec![Entry::error(
+                Path::from_iter([PathSegment::field("quantities"), PathSegment::Index(1)]),
+                ReservationInvalidity::Quantity(QuantityInvalidity::Max(2))
+            )],
+            reservation
+                .validate_with_env(&restaurant)
+                .unwrap_err()
+                .into_iter()
+                .collect::<Vec<_>>()
+        );
+        assert!(
+            Reservation::validated_from_with(
+                reservation,
+                &Restaurant {
+                    limits: Limits { max_quantity: 3 },
+                },
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn validate_with_env_within() {
+        let restaurant = Restaurant {
+            limits: Limits { max_quantity: 2 },
+        };
+        let reservation = Reservation {
+            quantities: vec![Quantity(3), Quantity(4), Quantity(5)],
+        };
+        let context = Context::<_>::fail_fast().validate_in(&reservation, &restaurant);
+        assert_eq!(
+            vec![Entry::error(
+                Path::from_iter([PathSegment::field("quantities"), PathSegment::Index(0)]),
+                ReservationInvalidity::Quantity(QuantityInvalidity::Max(2))
+            )],
+            context.into_iter().collect::<Vec<_>>()
+        );
+        let context =
+            Context::<ReservationInvalidity>::bounded(2).validate_in(&reservation, &restaurant);
+        assert_eq!((2, 1), (context.len(), context.omitted()));
+    }
+
+    #[test]
+    fn validate_in_inline_context() {
+        let limits = Limits { max_quantity: 2 };
+        let context = InlineContext::<_, 1>::default()
+            .validate_in(&Quantity(3), &limits)
+            .validate_in(&Quantity(2), &limits);
+        assert_eq!(
+            vec![Entry::error(Path::default(), QuantityInvalidity::Max(2))],
+            context.into_iter().collect::<Vec<_>>()
+        );
+    }
+}
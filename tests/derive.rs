@@ -0,0 +1,555 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+use std::borrow::Cow;
+
+use semval::{
+    context::Entry,
+    path::{Path, PathSegment},
+    prelude::*,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, NestedInvalidity)]
+enum LeafInvalidity {
+    Invalid,
+}
+
+#[derive(Debug, Clone)]
+struct Leaf(bool);
+
+impl Validate for Leaf {
+    type Invalidity = LeafInvalidity;
+
+    fn validate(&self) -> ValidationResult<Self::Invalidity> {
+        ValidationContext::new()
+            .invalidate_if(!self.0, LeafInvalidity::Invalid)
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, NestedInvalidity)]
+enum RecordInvalidity {
+    Required(LeafInvalidity),
+    Optional(LeafInvalidity),
+    Many(LeafInvalidity),
+    Borrowed(LeafInvalidity),
+}
+
+#[derive(Validate)]
+#[semval(invalidity = RecordInvalidity)]
+struct Record<'a> {
+    required: Leaf,
+    optional: Option<Leaf>,
+    many: Vec<Leaf>,
+    borrowed: Cow<'a, [Leaf]>,
+    #[allow(dead_code)]
+    #[semval(skip)]
+    name: String,
+}
+
+impl Record<'_> {
+    fn valid() -> Self {
+        Self {
+            required: Leaf(true),
+            optional: None,
+            many: vec![Leaf(true)],
+            borrowed: Cow::Owned(vec![Leaf(true)]),
+            name: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, NestedInvalidity)]
+enum WrapperInvalidity<V> {
+    Inner(V),
+}
+
+#[derive(Validate)]
+#[semval(invalidity = WrapperInvalidity<T::Invalidity>)]
+struct Wrapper<T>(#[semval(variant = Inner)] T);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChoiceInvalidity {
+    Single(LeafInvalidity),
+    PairLeft(LeafInvalidity),
+    PairRight(LeafInvalidity),
+    Custom(LeafInvalidity),
+}
+
+#[allow(dead_code)]
+#[derive(Validate)]
+#[semval(invalidity = ChoiceInvalidity)]
+enum Choice {
+    Single(Leaf),
+    Pair {
+        left: Leaf,
+        right: Leaf,
+    },
+    Tuple(#[semval(variant = Custom)] Leaf, #[semval(skip)] usize),
+    #[semval(skip)]
+    Skipped(Leaf),
+    Empty,
+}
+
+fn invalidities<T: Validate>(validated: &T) -> Vec<T::Invalidity> {
+    validated
+        .validate()
+        .err()
+        .map(|context| context.into_iter().map(|entry| entry.invalidity).collect())
+        .unwrap_or_default()
+}
+
+fn paths<T: Validate>(validated: &T) -> Vec<Path> {
+    validated
+        .validate()
+        .err()
+        .map(|context| context.into_iter().map(|entry| entry.path).collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn derive_struct() {
+    assert!(Record::valid().is_valid());
+
+    let record = Record {
+        required: Leaf(false),
+        optional: Some(Leaf(false)),
+        many: vec![Leaf(true), Leaf(false), Leaf(false)],
+        borrowed: Cow::Borrowed(&[Leaf(false)]),
+        ..Record::valid()
+    };
+    assert_eq!(
+        vec![
+            RecordInvalidity::Required(LeafInvalidity::Invalid),
+            RecordInvalidity::Optional(LeafInvalidity::Invalid),
+            RecordInvalidity::Many(LeafInvalidity::Invalid),
+            RecordInvalidity::Many(LeafInvalidity::Invalid),
+            RecordInvalidity::Borrowed(LeafInvalidity::Invalid),
+        ],
+        invalidities(&record)
+    );
+    assert_eq!(
+        vec![
+            Path::from_iter(["required"]),
+            Path::from_iter(["optional"]),
+            Path::from_iter([PathSegment::field("many"), PathSegment::Index(1)]),
+            Path::from_iter([PathSegment::field("many"), PathSegment::Index(2)]),
+            Path::from_iter([PathSegment::field("borrowed"), PathSegment::Index(0)]),
+        ],
+        paths(&record)
+    );
+}
+
+#[test]
+fn derive_fail_fast() {
+    let record = Record {
+        required: Leaf(false),
+        many: vec![Leaf(false), Leaf(false)],
+        ..Record::valid()
+    };
+    assert!(!record.is_valid());
+    let context = record.validate_fail_fast().unwrap_err();
+    assert_eq!(
+        vec![Entry::error(
+            Path::from_iter(["required"]),
+            RecordInvalidity::Required(LeafInvalidity::Invalid)
+        )],
+        context.into_iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn derive_count_invalidities() {
+    assert_eq!(0, Record::valid().count_invalidities());
+    let record = Record {
+        required: Leaf(false),
+        many: vec![Leaf(false), Leaf(true), Leaf(false)],
+        ..Record::valid()
+    };
+    assert_eq!(3, record.count_invalidities());
+    assert_eq!(
+        5,
+        Constrained {
+            name: "A".to_string(),
+            quantity: 0,
+            tags: None,
+            leaves: vec![Leaf(false)],
+        }
+        .count_invalidities()
+    );
+}
+
+#[test]
+fn derive_generic_tuple_struct() {
+    assert!(Wrapper(Leaf(true)).is_valid());
+    assert_eq!(
+        vec![WrapperInvalidity::Inner(LeafInvalidity::Invalid)],
+        invalidities(&Wrapper(Leaf(false)))
+    );
+    assert_eq!(
+        vec![WrapperInvalidity::Inner(LeafInvalidity::Invalid)],
+        invalidities(&Wrapper([Leaf(true), Leaf(false)].as_slice()))
+    );
+}
+
+#[test]
+fn derive_enum() {
+    assert!(Choice::Single(Leaf(true)).is_valid());
+    assert!(Choice::Skipped(Leaf(false)).is_valid());
+    assert!(Choice::Empty.is_valid());
+    assert!(Choice::Tuple(Leaf(true), 0).is_valid());
+    assert_eq!(
+        vec![ChoiceInvalidity::Single(LeafInvalidity::Invalid)],
+        invalidities(&Choice::Single(Leaf(false)))
+    );
+    assert_eq!(
+        vec![ChoiceInvalidity::PairRight(LeafInvalidity::Invalid)],
+        invalidities(&Choice::Pair {
+            left: Leaf(true),
+            right: Leaf(false)
+        })
+    );
+    assert_eq!(
+        vec![ChoiceInvalidity::Custom(LeafInvalidity::Invalid)],
+        invalidities(&Choice::Tuple(Leaf(false), 0))
+    );
+    assert_eq!(
+        vec![Path::from_iter(["right"])],
+        paths(&Choice::Pair {
+            left: Leaf(true),
+            right: Leaf(false)
+        })
+    );
+    assert_eq!(vec![Path::root()], paths(&Choice::Single(Leaf(false))));
+}
+
+#[derive(Validate, Invalidity)]
+#[semval(invalidity_derive(Clone, Copy, PartialEq, Eq, semval::NestedInvalidity))]
+struct Composite<'a> {
+    leaf: Leaf,
+    #[semval(skip_from)]
+    other_leaf: Leaf,
+    record: Option<Record<'a>>,
+}
+
+/// Wrappers that forward the invalidity type of their elements
+/// don't generate conflicting `From` conversions
+#[derive(Validate, Invalidity)]
+#[semval(invalidity_derive(PartialEq))]
+struct Wrappers {
+    leaf: Leaf,
+    boxed: Box<Leaf>,
+    shared: std::sync::Arc<Leaf>,
+    array: [Leaf; 2],
+    queue: std::collections::VecDeque<Leaf>,
+    map: std::collections::BTreeMap<String, Leaf>,
+}
+
+#[derive(Validate, Invalidity)]
+#[semval(
+    invalidity = GenericInvalidity<T>,
+    invalidity_derive(semval::NestedInvalidity)
+)]
+struct Generic<T> {
+    leaf: Leaf,
+    inner: Vec<T>,
+}
+
+#[test]
+fn derive_invalidity() {
+    let composite = Composite {
+        leaf: Leaf(false),
+        other_leaf: Leaf(false),
+        record: Some(Record {
+            required: Leaf(false),
+            ..Record::valid()
+        }),
+    };
+    assert_eq!(
+        vec![
+            CompositeInvalidity::Leaf(LeafInvalidity::Invalid),
+            CompositeInvalidity::OtherLeaf(LeafInvalidity::Invalid),
+            CompositeInvalidity::Record(RecordInvalidity::Required(LeafInvalidity::Invalid)),
+        ],
+        invalidities(&composite)
+    );
+    assert_eq!(
+        "Record(Required(Invalid))",
+        format!(
+            "{:?}",
+            CompositeInvalidity::Record(RecordInvalidity::Required(LeafInvalidity::Invalid))
+        )
+    );
+
+    // From conversions
+    assert_eq!(
+        CompositeInvalidity::Leaf(LeafInvalidity::Invalid),
+        LeafInvalidity::Invalid.into()
+    );
+    assert_eq!(
+        CompositeInvalidity::Record(RecordInvalidity::Many(LeafInvalidity::Invalid)),
+        RecordInvalidity::Many(LeafInvalidity::Invalid).into()
+    );
+    assert_eq!(
+        vec![CompositeInvalidity::Leaf(LeafInvalidity::Invalid)],
+        ValidationContext::new()
+            .validate(&Leaf(false))
+            .into_iter()
+            .map(|entry| entry.invalidity)
+            .collect::<Vec<CompositeInvalidity>>()
+    );
+    assert_eq!(
+        WrappersInvalidity::Leaf(LeafInvalidity::Invalid),
+        LeafInvalidity::Invalid.into()
+    );
+    assert_eq!(
+        vec![
+            Path::from_iter(["boxed"]),
+            Path::from_iter([PathSegment::field("array"), PathSegment::Index(1)])
+        ],
+        paths(&Wrappers {
+            leaf: Leaf(true),
+            boxed: Box::new(Leaf(false)),
+            shared: Leaf(true).into(),
+            array: [Leaf(true), Leaf(false)],
+            queue: [Leaf(true)].into(),
+            map: [("a".to_owned(), Leaf(true))].into(),
+        })
+    );
+    assert_eq!(
+        vec![Path::from_iter(["record", "required"])],
+        paths(&Composite {
+            leaf: Leaf(true),
+            other_leaf: Leaf(true),
+            record: Some(Record {
+                required: Leaf(false),
+                ..Record::valid()
+            }),
+        })
+    );
+}
+
+#[test]
+fn derive_generic_invalidity() {
+    let generic = Generic {
+        leaf: Leaf(true),
+        inner: vec![Wrapper(Leaf(false))],
+    };
+    assert_eq!(
+        "[Inner(Inner(Invalid))]",
+        format!("{:?}", invalidities(&generic))
+    );
+    let invalidity: GenericInvalidity<Wrapper<Leaf>> = LeafInvalidity::Invalid.into();
+    assert_eq!("Leaf(Invalid)", format!("{invalidity:?}"));
+}
+
+fn is_lowercase(value: &str) -> bool {
+    value.chars().all(char::is_lowercase)
+}
+
+#[derive(Validate, Invalidity)]
+#[semval(invalidity_derive(Clone, PartialEq, semval::NestedInvalidity))]
+struct Constrained {
+    #[semval(length(min = 2, max = 4), custom = is_lowercase)]
+    name: String,
+    #[semval(range(min = 1, max = 10))]
+    quantity: u8,
+    #[semval(required, length(max = 1))]
+    tags: Option<Vec<String>>,
+    #[semval(non_empty(variant = NoLeaves), nested)]
+    leaves: Vec<Leaf>,
+}
+
+impl Constrained {
+    fn valid() -> Self {
+        Self {
+            name: "ab".to_string(),
+            quantity: 1,
+            tags: Some(vec![]),
+            leaves: vec![Leaf(true)],
+        }
+    }
+}
+
+#[derive(Validate, Invalidity)]
+#[semval(invalidity_derive(Clone, PartialEq))]
+struct Code(#[semval(length(min = 3))] &'static str);
+
+#[test]
+fn derive_constraints() {
+    use semval::constraint::{Custom, Length, NonEmpty, Range, Required};
+
+    assert!(Constrained::valid().is_valid());
+    assert!(
+        Constrained {
+            name: "abcd".to_string(),
+            quantity: 10,
+            tags: Some(vec![String::new()]),
+            ..Constrained::valid()
+        }
+        .is_valid()
+    );
+    assert_eq!(
+        vec![
+            ConstrainedInvalidity::NameLength(Length {
+                min: Some(2),
+                max: Some(4)
+            }),
+            ConstrainedInvalidity::NameCustom(Custom {
+                function: "is_lowercase"
+            }),
+            ConstrainedInvalidity::QuantityRange(Range {
+                min: Some(1),
+                max: Some(10)
+            }),
+            ConstrainedInvalidity::TagsRequired(Required),
+            ConstrainedInvalidity::NoLeaves(NonEmpty),
+        ],
+        invalidities(&Constrained {
+            name: "A".to_string(),
+            quantity: 0,
+            tags: None,
+            leaves: vec![],
+        })
+    );
+    assert_eq!(
+        vec![
+            ConstrainedInvalidity::TagsLength(Length {
+                min: None,
+                max: Some(1)
+            }),
+            ConstrainedInvalidity::Leaves(LeafInvalidity::Invalid),
+        ],
+        invalidities(&Constrained {
+            tags: Some(vec![String::new(), String::new()]),
+            leaves: vec![Leaf(false)],
+            ..Constrained::valid()
+        })
+    );
+
+    assert!(Code("abc").is_valid());
+    assert_eq!(
+        vec![CodeInvalidity::Length(Length {
+            min: Some(3),
+            max: None
+        })],
+        invalidities(&Code("ab"))
+    );
+}
+
+#[cfg(feature = "regex")]
+#[derive(Validate, Invalidity)]
+#[semval(invalidity_derive(Clone, PartialEq))]
+struct Patterned {
+    #[semval(pattern = "^[a-z]+@[a-z]+$")]
+    email: String,
+    #[semval(pattern(regex = "^[0-9]+$", variant = NotNumeric))]
+    phone: Option<String>,
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn derive_pattern_constraints() {
+    use semval::constraint::Pattern;
+
+    assert!(
+        Patterned {
+            email: "a@b".to_string(),
+            phone: None,
+        }
+        .is_valid()
+    );
+    assert_eq!(
+        vec![
+            PatternedInvalidity::EmailPattern(Pattern {
+                regex: "^[a-z]+@[a-z]+$"
+            }),
+            PatternedInvalidity::NotNumeric(Pattern { regex: "^[0-9]+$" }),
+        ],
+        invalidities(&Patterned {
+            email: "a@b@c".to_string(),
+            phone: Some("+49".to_string()),
+        })
+    );
+}
+
+#[derive(Debug, PartialEq, NestedInvalidity)]
+enum OrderInvalidity<T> {
+    Item(T),
+    Composite {
+        position: usize,
+        #[semval(source)]
+        invalidity: CompositeInvalidity,
+    },
+    #[semval(leaf)]
+    Quantity(u32),
+    Empty,
+}
+
+#[derive(Debug, PartialEq, NestedInvalidity)]
+#[semval(leaf)]
+struct Tag(&'static str);
+
+#[test]
+fn derive_nested_invalidity() {
+    let composite = Composite {
+        leaf: Leaf(true),
+        other_leaf: Leaf(false),
+        record: Some(Record {
+            many: vec![Leaf(false)],
+            ..Record::valid()
+        }),
+    };
+    let context = ValidationContext::<OrderInvalidity<LeafInvalidity>>::new()
+        .validate_with(&composite, |invalidity| OrderInvalidity::Composite {
+            position: 1,
+            invalidity,
+        })
+        .invalidate(OrderInvalidity::Item(LeafInvalidity::Invalid))
+        .invalidate(OrderInvalidity::Quantity(0))
+        .invalidate(OrderInvalidity::Empty);
+    assert_eq!(
+        vec![3, 4, 2, 1, 1],
+        context.chains().map(Iterator::count).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        Some(&OrderInvalidity::Composite {
+            position: 1,
+            invalidity: CompositeInvalidity::OtherLeaf(LeafInvalidity::Invalid),
+        }),
+        context.first().map(|entry| &entry.invalidity)
+    );
+    assert_eq!(
+        vec![&OrderInvalidity::Quantity(0), &OrderInvalidity::Empty],
+        context
+            .root_causes()
+            .filter_map(<dyn NestedInvalidity>::downcast_ref::<OrderInvalidity<LeafInvalidity>>)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(3, context.find::<LeafInvalidity>().count());
+    assert!(context.contains_kind::<RecordInvalidity>());
+    assert_eq!(
+        3,
+        context.group_by_root()[&std::any::TypeId::of::<LeafInvalidity>()].len()
+    );
+    let tag: &dyn NestedInvalidity = &Tag("a");
+    assert!(tag.source().is_none());
+    assert_eq!(Some(&Tag("a")), tag.downcast_ref());
+
+    let constrained = Constrained {
+        quantity: 0,
+        ..Constrained::valid()
+    };
+    let context = constrained.validate().unwrap_err();
+    assert!(context.contains_kind::<semval::constraint::Range<u8>>());
+    let generic = Generic {
+        leaf: Leaf(true),
+        inner: vec![Wrapper(Leaf(false))],
+    };
+    assert!(
+        generic
+            .validate()
+            .unwrap_err()
+            .contains_kind::<LeafInvalidity>()
+    );
+}
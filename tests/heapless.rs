@@ -0,0 +1,171 @@
+// SPDX-FileCopyrightText: slowtec GmbH
+// SPDX-License-Identifier: MPL-2.0
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use semval::{
+    context::{FixedContext, Storage},
+    prelude::*,
+};
+
+/// Counts the allocations of the current thread
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+#[expect(unsafe_code, reason = "forwards to the system allocator")]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        // SAFETY: Forwarded unmodified
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: Forwarded unmodified
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.get();
+    let result = f();
+    (result, ALLOCATIONS.get() - before)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LeafInvalidity {
+    Zero,
+}
+
+struct Leaf(u8);
+
+impl Validate for Leaf {
+    type Invalidity = LeafInvalidity;
+
+    fn validate(&self) -> ValidationResult<Self::Invalidity> {
+        self.validate_within(ValidationContext::new()).into()
+    }
+
+    fn validate_within<S>(
+        &self,
+        context: ValidationContext<Self::Invalidity, S>,
+    ) -> ValidationContext<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        context.invalidate_if(self.0 == 0, LeafInvalidity::Zero)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RootInvalidity {
+    Leaf(LeafInvalidity),
+    Empty,
+}
+
+struct Root {
+    leaves: [Leaf; 4],
+    pairs: [(Leaf, Leaf); 2],
+}
+
+impl Validate for Root {
+    type Invalidity = RootInvalidity;
+
+    fn validate(&self) -> ValidationResult<Self::Invalidity> {
+        self.validate_within(ValidationContext::new()).into()
+    }
+
+    fn validate_within<S>(
+        &self,
+        context: ValidationContext<Self::Invalidity, S>,
+    ) -> ValidationContext<Self::Invalidity, S>
+    where
+        S: Storage<Self::Invalidity>,
+    {
+        context
+            .invalidate_if(self.leaves.is_empty(), RootInvalidity::Empty)
+            .validate_at_with("leaves", &self.leaves, RootInvalidity::Leaf)
+            .scoped("pairs", |context| {
+                self.pairs
+                    .iter()
+                    .enumerate()
+                    .fold(context, |context, (index, (first, second))| {
+                        context.scoped(index, |context| {
+                            context
+                                .validate_at_with(0, first, RootInvalidity::Leaf)
+                                .validate_at_with(1, second, RootInvalidity::Leaf)
+                        })
+                    })
+            })
+    }
+}
+
+#[test]
+fn fixed_context_does_not_allocate() {
+    assert_eq!(1, count_allocations(|| Box::new(0)).1);
+
+    let leaves = [Leaf(0), Leaf(1), Leaf(0), Leaf(0)];
+    let (context, allocations) =
+        count_allocations(|| FixedContext::<LeafInvalidity, 2>::default().validate(&leaves));
+    assert_eq!(0, allocations);
+    assert_eq!((2, 1), (context.len(), context.omitted()));
+
+    // Nested paths with up to `INLINE_PATH_LEN` segments are stored inline
+    let root = Root {
+        leaves,
+        pairs: [(Leaf(1), Leaf(0)), (Leaf(0), Leaf(0))],
+    };
+    let (context, allocations) =
+        count_allocations(|| root.validate_within(FixedContext::<_, 4>::default()));
+    assert_eq!(0, allocations);
+    assert_eq!((4, 2), (context.len(), context.omitted()));
+    assert_eq!(
+        "leaves[0]: Leaf(Zero); leaves[2]: Leaf(Zero); leaves[3]: Leaf(Zero); pairs[0][1]: \
+         Leaf(Zero); 2 more invalidities omitted",
+        context.to_string()
+    );
+}
+
+/// Only implements `validate()` and records an invalidity for each zero
+struct Digits(&'static [u8]);
+
+impl Validate for Digits {
+    type Invalidity = LeafInvalidity;
+
+    fn validate(&self) -> ValidationResult<Self::Invalidity> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, digit)| **digit == 0)
+            .fold(ValidationContext::new(), |context, (index, _)| {
+                context.invalidate_at(index, LeafInvalidity::Zero)
+            })
+            .into()
+    }
+}
+
+#[test]
+fn fixed_context_allocates_for_validate_only() {
+    // The invalidities are collected in the inline capacity of the default storage
+    let digits = Digits(&[0, 1, 0, 0]);
+    let (context, allocations) =
+        count_allocations(|| FixedContext::<LeafInvalidity, 2>::default().validate(&digits));
+    assert_eq!(0, allocations);
+    assert_eq!((2, 1), (context.len(), context.omitted()));
+
+    // Exceeding the inline capacity of the default storage allocates
+    let digits = Digits(&[0; 9]);
+    let (context, allocations) =
+        count_allocations(|| FixedContext::<LeafInvalidity, 2>::default().validate(&digits));
+    assert_ne!(0, allocations);
+    assert_eq!((2, 7), (context.len(), context.omitted()));
+}